@@ -0,0 +1,202 @@
+// Generate the DAYS table from the #[advent_day(N)] attributes in src/dNN.
+// Each src/dNN/mod.rs (or src/dNN.rs) must mark exactly one struct with
+// #[advent_day(NN)]; anything else fails the build. Days without a module are
+// left out of the table and reported as unimplemented.
+//
+// With the embed-inputs feature, build every src/dXX/input.txt (and
+// answers.txt) into the binary so it can run anywhere.
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const LAST_DAY: usize = 25;
+
+struct Registered {
+    day: usize,
+    module: String,
+    path: PathBuf,
+    name: String,
+}
+
+// The day number of a module named dNN.
+fn module_day(module: &str) -> Option<usize> {
+    let digits = module.strip_prefix('d')?;
+    match digits.len() == 2 && digits.bytes().all(|b| b.is_ascii_digit()) {
+        true => digits.parse().ok(),
+        false => None,
+    }
+}
+
+// The (day, struct name) of each #[advent_day(N)] in source.
+fn find_attributes(source: &str) -> Result<Vec<(usize, String)>, String> {
+    let mut found = Vec::new();
+    let lines: Vec<&str> = source.lines().map(str::trim).filter(|line| !line.starts_with("//")).collect();
+    for (index, line) in lines.iter().enumerate() {
+        let arg = match line.strip_prefix("#[advent_day(").and_then(|rest| rest.split_once(")]")) {
+            Some((arg, _)) => arg.trim(),
+            None => continue,
+        };
+        let day = arg.parse().map_err(|_| format!("#[advent_day({})]: expected a day number", arg))?;
+        let name = lines[index + 1..].iter()
+            .filter(|line| !line.starts_with("#["))
+            .find_map(|line| {
+                let mut words = line.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .filter(|word| !word.is_empty());
+                words.find(|word| *word == "struct").and(words.next())
+            })
+            .ok_or_else(|| format!("#[advent_day({})] is not on a struct", day))?;
+        found.push((day, name.to_string()));
+    }
+    Ok(found)
+}
+
+fn register(src: &Path) -> Result<Vec<Registered>, String> {
+    let mut days: Vec<Registered> = Vec::new();
+    for entry in fs::read_dir(src).map_err(|e| format!("{}: {}", src.display(), e))? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        let module = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default().to_string();
+        let module_day = match module_day(&module) {
+            Some(day) => day,
+            None => continue,
+        };
+        let path = match path.is_dir() {
+            true => path.join("mod.rs"),
+            false if path.extension() == Some("rs".as_ref()) => path,
+            false => continue,
+        };
+        let source = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let mut found = find_attributes(&source).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let (day, name) = match found.len() {
+            1 => found.remove(0),
+            0 => return Err(format!("{}: no #[advent_day({})] struct", path.display(), module_day)),
+            _ => return Err(format!("{}: more than one #[advent_day]", path.display())),
+        };
+        if day != module_day {
+            return Err(format!("{}: #[advent_day({})] in module {}", path.display(), day, module));
+        }
+        if !(1..=LAST_DAY).contains(&day) {
+            return Err(format!("{}: day {} is not from 1 to {}", path.display(), day, LAST_DAY));
+        }
+        if let Some(other) = days.iter().find(|other| other.day == day) {
+            return Err(format!("day {} is registered by both {} and {}",
+                               day, other.path.display(), path.display()));
+        }
+        days.push(Registered { day, module, path, name });
+    }
+    days.sort_by_key(|registered| registered.day);
+    Ok(days)
+}
+
+fn generate(days: &[Registered]) -> String {
+    let mut code = String::from("// Generated by build.rs from the #[advent_day(N)] attributes in src/dNN.\n\n");
+    for registered in days {
+        code += &format!("#[path = {:?}]\nmod {};\n", registered.path.display().to_string(), registered.module);
+    }
+    let max_day = days.last().map_or(0, |registered| registered.day);
+    code += &format!("\npub const DAYS: [Option<&dyn Solver>; {}] = [\n", max_day);
+    for day in 1..=max_day {
+        code += &match days.iter().find(|registered| registered.day == day) {
+            Some(registered) => format!("    Some(&{}::{}{{}}),\n", registered.module, registered.name),
+            None => "    None,\n".to_string(),
+        };
+    }
+    code + "];\n"
+}
+
+// FNV-1a of every .rs file under dir with its path, for the answer cache.
+fn source_hash(dir: &Path) -> u64 {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.extension() == Some("rs".as_ref()) {
+                paths.push(path);
+            }
+        }
+    }
+    paths.sort();
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for path in paths {
+        let relative = path.strip_prefix(dir).unwrap().to_string_lossy().into_owned();
+        for byte in relative.into_bytes().into_iter().chain(fs::read(&path).unwrap()) {
+            hash = (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3);
+        }
+    }
+    hash
+}
+
+// Pass the git revision being built on to the run history, if this is a checkout.
+fn record_revision() {
+    let root = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let git = root.join(".git");
+    // HEAD moves on checkout and the index on commit; src changes are already watched.
+    for file in ["HEAD", "index"] {
+        if git.join(file).exists() {
+            println!("cargo:rerun-if-changed={}", git.join(file).display());
+        }
+    }
+    let describe = Command::new("git").args(["describe", "--always", "--dirty"]).current_dir(&root).output();
+    if let Ok(output) = describe {
+        let revision = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if output.status.success() && !revision.is_empty() {
+            println!("cargo:rustc-env=ADVENT2021_REVISION={}", revision);
+        }
+    }
+}
+
+// (day, path) for each file named name under src/dXX, in day order.
+fn day_files(src: &Path, name: &str) -> Vec<(u8, PathBuf)> {
+    (1..=25u8)
+        .map(|day| (day, src.join(format!("d{:02}", day)).join(name)))
+        .filter(|(_, path)| path.is_file())
+        .collect()
+}
+
+fn table(name: &str, kind: &str, files: &[(u8, PathBuf)]) -> String {
+    let mut table = format!("pub const {}: &[(u8, {})] = &[\n", name, kind);
+    for (day, path) in files {
+        let include = if kind == "&str" { "include_str!" } else { "include_bytes!" };
+        table += &format!("    ({}, {}({:?})),\n", day, include, path.display().to_string());
+    }
+    table + "];\n"
+}
+
+// The new-day template filled in as day 25, for lib.rs to compile in tests.
+fn new_day(src: &Path) -> String {
+    let path = src.join("new_day.rs.in");
+    let template = fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+    template.replace("{NN}", "25").replace("{N}", "25")
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    let src = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("src");
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    // Rebuild when days are added, as well as when they change.
+    println!("cargo:rerun-if-changed=src");
+    let days = register(&src).unwrap_or_else(|error| panic!("registering days: {}", error));
+    fs::write(out_dir.join("days.rs"), generate(&days)).unwrap();
+    fs::write(out_dir.join("new_day.rs"), new_day(&src)).unwrap();
+    record_revision();
+    println!("cargo:rustc-env=ADVENT2021_SOURCE_HASH={:016x}", source_hash(&src));
+    if env::var_os("CARGO_FEATURE_EMBED_INPUTS").is_none() {
+        return;
+    }
+    let inputs = day_files(&src, "input.txt");
+    let answers = day_files(&src, "answers.txt");
+    for day in 1..=25 {
+        let dir = src.join(format!("d{:02}", day));
+        if dir.is_dir() {
+            println!("cargo:rerun-if-changed={}", dir.display());
+        }
+    }
+    for (_, path) in inputs.iter().chain(&answers) {
+        println!("cargo:rerun-if-changed={}", path.display());
+    }
+    let code = table("INPUTS", "&[u8]", &inputs) + &table("ANSWERS", "&str", &answers);
+    fs::write(out_dir.join("embedded.rs"), code).unwrap();
+}
@@ -0,0 +1,62 @@
+/* C interface to the Advent of Code 2021 solvers.
+ *
+ * Link against the cdylib from `cargo build --release`
+ * (target/release/libadvent2021.so, .dylib, or advent2021.dll).
+ *
+ * No call lets a Rust panic unwind into C: a panicking solver returns
+ * ADVENT2021_PANIC with the panic message instead.
+ */
+#ifndef ADVENT2021_H
+#define ADVENT2021_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Status codes in advent2021_result.status. */
+#define ADVENT2021_OK 0
+#define ADVENT2021_NO_SUCH_DAY 1           /* no solution for that day (yet) */
+#define ADVENT2021_NO_SUCH_PART 2          /* parts are 1 and 2 */
+#define ADVENT2021_PARAM 3                 /* unknown parameter or bad value */
+#define ADVENT2021_PARSE 4                 /* malformed input */
+#define ADVENT2021_SOLVE 5                 /* the solution failed on this input */
+#define ADVENT2021_INVALID_ARGUMENT 6      /* NULL pointer, non-UTF-8 input, ... */
+#define ADVENT2021_PANIC 7                 /* the solver panicked */
+
+typedef struct advent2021_result {
+    int32_t status;
+    /* The answer on success, else NULL. */
+    char *answer;
+    /* What went wrong on failure, else NULL. */
+    char *error;
+    /* Time spent parsing the input and solving the part, in nanoseconds. */
+    uint64_t parse_ns;
+    uint64_t time_ns;
+} advent2021_result;
+
+/* Write up to len implemented days into days (which may be NULL), returning
+ * how many there are. */
+size_t advent2021_days(uint8_t *days, size_t len);
+
+/* Solve one part of a day for the input_len bytes of UTF-8 at input.
+ *
+ * params holds nparams NUL-terminated "NAME=VALUE" strings setting the day's
+ * parameters, like "steps=40"; either may be NULL/0 for none.
+ *
+ * Never returns NULL. The result and its strings belong to the caller, who
+ * frees them with advent2021_free_result. */
+advent2021_result *advent2021_solve(uint8_t day, uint8_t part,
+                                    const uint8_t *input, size_t input_len,
+                                    const char *const *params, size_t nparams);
+
+/* Free a result from advent2021_solve. NULL is ignored. */
+void advent2021_free_result(advent2021_result *result);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* ADVENT2021_H */
@@ -0,0 +1,30 @@
+// #[advent_day(N)] marks the struct implementing Day for day N.
+//
+// The attribute itself only checks its argument; build.rs finds the attributes
+// in src/dNN and generates the DAYS table from them.
+
+use proc_macro::{TokenStream, TokenTree};
+
+fn error(message: &str, item: TokenStream) -> TokenStream {
+    let mut tokens: TokenStream = format!("compile_error!({:?});", message).parse().unwrap();
+    tokens.extend(item);
+    tokens
+}
+
+#[proc_macro_attribute]
+pub fn advent_day(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let attr: Vec<TokenTree> = attr.into_iter().collect();
+    let day = match attr.as_slice() {
+        [TokenTree::Literal(day)] => day.to_string().parse::<u8>().ok(),
+        _ => None,
+    };
+    if !matches!(day, Some(1..=25)) {
+        return error("expected #[advent_day(N)] with N from 1 to 25", item);
+    }
+    let is_struct = item.clone().into_iter()
+        .any(|token| matches!(&token, TokenTree::Ident(ident) if ident.to_string() == "struct"));
+    if !is_struct {
+        return error("#[advent_day] goes on the struct implementing Day", item);
+    }
+    item
+}
@@ -0,0 +1,145 @@
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde_json::{json, Map, Value};
+
+use crate::report::Record;
+
+// Baselines are kept in ./baselines/NAME.json.
+const BASELINE_DIR: &str = "baselines";
+
+// Phases compared against a baseline; input time depends on the disk, not the code.
+const PHASES: [&str; 3] = ["parse", "part1", "part2"];
+
+// Saved timings for each day run, keyed by day (and example, if any).
+//
+//   {"days": {"4": {"input_ns": 32614, "parse_ns": 1311430, "part1_ns": ..., "part2_ns": ...}}}
+//
+pub struct Baseline {
+    pub name: String,
+    days: Map<String, Value>,
+}
+
+// How one phase's time compares to the baseline.
+pub struct Change {
+    pub phase: &'static str,
+    pub baseline: Duration,
+    pub time: Duration,
+    pub slower: bool,
+}
+
+impl Change {
+    // Percent change from the baseline; positive is slower.
+    pub fn percent(&self) -> f64 {
+        (self.time.as_nanos() as f64 / self.baseline.as_nanos() as f64 - 1.0) * 100.0
+    }
+}
+
+pub struct Comparison {
+    pub baseline: String,
+    pub threshold: f64,
+    // Phases with no baseline (or not run) are left out.
+    pub changes: Vec<Change>,
+}
+
+impl Comparison {
+    // Number of phases slower than the threshold allows.
+    pub fn regressions(&self) -> usize {
+        self.changes.iter().filter(|change| change.slower).count()
+    }
+}
+
+// Check a name keeps its baseline inside BASELINE_DIR.
+pub fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.contains("..") {
+        return Err(format!("invalid baseline name '{}': use a plain name without '/', '\\' or '..'", name));
+    }
+    Ok(())
+}
+
+fn path(name: &str) -> Result<PathBuf, String> {
+    check_name(name)?;
+    Ok(PathBuf::from(BASELINE_DIR).join(format!("{}.json", name)))
+}
+
+fn key(record: &Record) -> String {
+    match &record.example {
+        Some(example) => format!("{}:{}", record.day, example),
+        None => record.day.to_string(),
+    }
+}
+
+fn nanos(time: Duration) -> u64 {
+    time.as_nanos() as u64
+}
+
+// Phase time as recorded: None if the phase was skipped.
+fn phase_time(record: &Record, phase: &str) -> Option<Duration> {
+    match phase {
+        "parse" => Some(record.parse),
+        "part1" => record.parts[0].as_ref().map(|part| part.time),
+        _ => record.parts[1].as_ref().map(|part| part.time),
+    }
+}
+
+impl Baseline {
+    // Load a saved baseline, which must exist.
+    pub fn load(name: &str) -> Result<Baseline, Box<dyn Error>> {
+        let path = path(name)?;
+        let text = fs::read_to_string(&path).map_err(|error| match error.kind() {
+            io::ErrorKind::NotFound => format!("no baseline '{}' ({} not found)", name, path.display()),
+            _ => format!("cannot read {}: {}", path.display(), error),
+        })?;
+        let value: Value = serde_json::from_str(&text)
+            .map_err(|error| format!("{}: {}", path.display(), error))?;
+        let days = value.get("days").and_then(Value::as_object).cloned()
+            .ok_or_else(|| format!("{}: missing \"days\"", path.display()))?;
+        Ok(Baseline { name: name.to_string(), days })
+    }
+
+    // Update a baseline with the days just run, keeping days that weren't.
+    pub fn save(name: &str, records: &[Record]) -> Result<PathBuf, Box<dyn Error>> {
+        // Only a missing baseline starts afresh; one we can't read would be clobbered.
+        let mut baseline = match fs::metadata(path(name)?) {
+            Err(error) if error.kind() == io::ErrorKind::NotFound =>
+                Baseline { name: name.to_string(), days: Map::new() },
+            _ => Baseline::load(name)?,
+        };
+        // --validate runs only parse, so they have no times worth keeping.
+        for record in records.iter().filter(|record| record.error.is_none() && record.problems.is_none()) {
+            let mut times = Map::new();
+            times.insert("input_ns".into(), json!(nanos(record.input)));
+            for phase in PHASES.iter() {
+                if let Some(time) = phase_time(record, phase) {
+                    times.insert(format!("{}_ns", phase), json!(nanos(time)));
+                }
+            }
+            baseline.days.insert(key(record), Value::Object(times));
+        }
+        let path = path(name)?;
+        fs::create_dir_all(BASELINE_DIR)?;
+        let text = serde_json::to_string_pretty(&json!({ "days": baseline.days }))?;
+        fs::write(&path, text + "\n").map_err(|error| format!("cannot write {}: {}", path.display(), error))?;
+        Ok(path)
+    }
+
+    // Compare a day's times against the baseline. A phase is slower if it took
+    // more than threshold percent longer.
+    pub fn compare(&self, record: &Record, threshold: f64) -> Option<Comparison> {
+        let saved = self.days.get(&key(record))?;
+        let changes = PHASES.iter().filter_map(|phase| {
+            let baseline = saved.get(format!("{}_ns", phase)).and_then(Value::as_u64)?;
+            let time = phase_time(record, phase)?;
+            if baseline == 0 {
+                return None;
+            }
+            let baseline = Duration::from_nanos(baseline);
+            let slower = time.as_nanos() as f64 > baseline.as_nanos() as f64 * (1.0 + threshold / 100.0);
+            Some(Change { phase, baseline, time, slower })
+        }).collect();
+        Some(Comparison { baseline: self.name.clone(), threshold, changes })
+    }
+}
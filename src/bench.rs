@@ -0,0 +1,96 @@
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+use advent2021::{util, DayResult, Solver};
+
+use crate::cli;
+
+// Summary of repeated timings of one phase.
+#[derive(Debug, Clone, Copy)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub stddev: Duration,
+}
+
+impl Stats {
+    fn from(mut times: Vec<Duration>) -> Stats {
+        times.sort();
+        let n = times.len();
+        let median = if n % 2 == 1 {
+            times[n / 2]
+        } else {
+            (times[n / 2 - 1] + times[n / 2]) / 2
+        };
+        let nanos: Vec<f64> = times.iter().map(|time| time.as_nanos() as f64).collect();
+        let mean = nanos.iter().sum::<f64>() / n as f64;
+        // Sample standard deviation; zero for a single run.
+        let variance = if n > 1 {
+            nanos.iter().map(|time| (time - mean).powi(2)).sum::<f64>() / (n - 1) as f64
+        } else {
+            0.0
+        };
+        Stats {
+            min: times[0],
+            median,
+            mean: Duration::from_nanos(mean.round() as u64),
+            stddev: Duration::from_nanos(variance.sqrt().round() as u64),
+        }
+    }
+}
+
+// Timings from benchmarking a day, per phase.
+pub struct Bench {
+    pub runs: usize,
+    pub parse: Stats,
+    // None for a skipped part.
+    pub parts: [Option<Stats>; 2],
+}
+
+// Time parse and each selected part runs times over, after a warm-up run.
+//
+// The input is already in memory, so only parsing and solving are measured.
+// The warm-up run gives the answers and any verbose output; verbose output
+// from the timed runs is discarded.
+pub fn run(day: &dyn Solver, input: &[u8], opts: &cli::Cli, runs: usize)
+    -> Result<(DayResult, Bench), Box<dyn Error>>
+{
+    let result = day.solve(&mut &input[..], &opts.args, opts.part)?;
+    let answers = [result.part1.as_ref(), result.part2.as_ref()]
+        .map(|part| part.map(|part| part.answer.clone()));
+    let mut parse_times = Vec::with_capacity(runs);
+    let mut part_times = [Vec::with_capacity(runs), Vec::with_capacity(runs)];
+    let (timed, _) = util::capture_verbose(|| -> Result<(), Box<dyn Error>> {
+        for _ in 0..runs {
+            let clock = Instant::now();
+            let parsed = day.parse(&mut &input[..], &opts.args)?;
+            parse_times.push(clock.elapsed());
+            for (index, expected) in answers.iter().enumerate() {
+                let expected = match expected {
+                    Some(expected) => expected,
+                    None => continue,
+                };
+                let clock = Instant::now();
+                let answer = match index {
+                    0 => day.part1(parsed.as_ref(), &opts.args)?,
+                    _ => day.part2(parsed.as_ref(), &opts.args)?,
+                };
+                part_times[index].push(clock.elapsed());
+                if answer != *expected {
+                    return Err(format!("part {} answer changed between runs: {} then {}",
+                                       index + 1, expected, answer).into());
+                }
+            }
+        }
+        Ok(())
+    });
+    timed?;
+    let [part1, part2] = part_times;
+    let bench = Bench {
+        runs,
+        parse: Stats::from(parse_times),
+        parts: [part1, part2].map(|times| if times.is_empty() { None } else { Some(Stats::from(times)) }),
+    };
+    Ok((result, bench))
+}
@@ -0,0 +1,108 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use advent2021::params::Arg;
+use advent2021::{DayResult, PartResult, Solver};
+
+use crate::{cli, history};
+
+// Hash of the source the binary was built from, so editing a solution invalidates its answers.
+const SOURCE: &str = env!("ADVENT2021_SOURCE_HASH");
+
+// ~/.cache/advent2021/answers, respecting $XDG_CACHE_HOME.
+fn dir() -> PathBuf {
+    let cache = match env::var_os("XDG_CACHE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME").unwrap_or_default()).join(".cache"),
+    };
+    cache.join("advent2021").join("answers")
+}
+
+// Key for a part's answer: the day, part, input, the parameters the day takes, and the version.
+fn key(solver: &dyn Solver, day: usize, part: usize, input: &[u8], args: &[Arg]) -> String {
+    let mut key = format!("{} {} {} {}", env!("CARGO_PKG_VERSION"), SOURCE, day, part);
+    for param in solver.params() {
+        // The last one given wins, as in params::get.
+        match args.iter().rev().find(|arg| arg.name == param.name) {
+            Some(arg) => key += &format!(" {}", arg),
+            None => key += &format!(" {}={}", param.name, param.default),
+        }
+    }
+    let mut bytes = key.into_bytes();
+    bytes.push(b'\n');
+    bytes.extend_from_slice(input);
+    format!("{:016x}", history::hash(&bytes))
+}
+
+fn get(key: &str) -> Option<String> {
+    fs::read_to_string(dir().join(key)).ok()
+}
+
+fn put(key: &str, answer: &str) -> Result<(), Box<dyn Error>> {
+    let dir = dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    // Write then rename, so a parallel run never reads half an answer.
+    let temp = dir.join(format!("{}.{}.tmp", key, std::process::id()));
+    fs::write(&temp, answer).map_err(|e| format!("{}: {}", temp.display(), e))?;
+    fs::rename(&temp, dir.join(key)).map_err(|e| format!("{}: {}", temp.display(), e))?;
+    Ok(())
+}
+
+// Solve a day through the cache, returning which parts' answers came from it.
+//
+// With --cache, parts already cached aren't run, and the day isn't even parsed if
+// every part is cached. With --no-cache, everything is run and the cache refreshed.
+pub fn solve(solver: &dyn Solver, day: usize, input: &[u8], opts: &cli::Cli)
+    -> Result<(DayResult, [bool; 2]), Box<dyn Error>>
+{
+    let parts: Vec<usize> = match opts.part {
+        Some(part) => vec![part],
+        None => vec![1, 2],
+    };
+    let keys: Vec<String> = (1..=2).map(|part| key(solver, day, part, input, &opts.args)).collect();
+    let mut cached = [None, None];
+    if opts.cache {
+        for &part in &parts {
+            cached[part - 1] = get(&keys[part - 1]);
+        }
+    }
+    let missing: Vec<usize> = parts.iter().copied().filter(|part| cached[part - 1].is_none()).collect();
+    let mut result = match missing.as_slice() {
+        [] => DayResult { parse: Duration::new(0, 0), part1: None, part2: None },
+        [part] => solver.solve(&mut &input[..], &opts.args, Some(*part))?,
+        _ => solver.solve(&mut &input[..], &opts.args, None)?,
+    };
+    for (index, solved) in [&result.part1, &result.part2].iter().enumerate() {
+        if let Some(solved) = solved {
+            if let Err(error) = put(&keys[index], &solved.answer) {
+                eprintln!("WARNING: answer not cached: {}", error);
+            }
+        }
+    }
+    let from_cache = [cached[0].is_some(), cached[1].is_some()];
+    let [part1, part2] = cached;
+    let cached = |answer: String| PartResult { answer, time: Duration::new(0, 0) };
+    result.part1 = result.part1.or_else(|| part1.map(cached));
+    result.part2 = result.part2.or_else(|| part2.map(cached));
+    Ok((result, from_cache))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_cover_what_changes_answers() {
+        let day6 = advent2021::solver(5).unwrap();
+        let arg = |s: &str| s.parse::<Arg>().unwrap();
+        let base = key(day6, 6, 1, b"3,4\n", &[]);
+        assert_eq!(base, key(day6, 6, 1, b"3,4\n", &[arg("days=80")]));
+        assert_eq!(base, key(day6, 6, 1, b"3,4\n", &[arg("days=18"), arg("days=80")]));
+        assert_ne!(base, key(day6, 6, 1, b"3,4\n", &[arg("days=18")]));
+        assert_ne!(base, key(day6, 6, 2, b"3,4\n", &[]));
+        assert_ne!(base, key(day6, 6, 1, b"3,5\n", &[]));
+    }
+}
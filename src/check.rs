@@ -0,0 +1,96 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// Outcome of checking one part's answer against the expected answer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Status {
+    Pass,
+    Fail,
+    Unknown,
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        f.pad(match self {
+            Status::Pass => "PASS",
+            Status::Fail => "FAIL",
+            Status::Unknown => "UNKNOWN",
+        })
+    }
+}
+
+// Expected answers for both parts of a day.
+//
+// The file format mirrors the normal output:
+//
+//   Part 1: 1759
+//   Part 2:
+//     0 | ...multi-line answers continue until the next part...
+//
+#[derive(Debug, Default)]
+pub struct Answers {
+    parts: [Option<String>; 2],
+}
+
+// Drop trailing whitespace and surrounding blank lines, so multi-line answers
+// compare equal however they were pasted.
+fn normalize(answer: &str) -> String {
+    let lines: Vec<&str> = answer.lines().map(|line| line.trim_end()).collect();
+    let first = lines.iter().position(|line| !line.is_empty()).unwrap_or(lines.len());
+    let last = lines.iter().rposition(|line| !line.is_empty()).map_or(first, |last| last + 1);
+    lines[first..last].join("\n")
+}
+
+impl Answers {
+    pub fn from(text: &str) -> Answers {
+        let mut answers = Answers::default();
+        let mut current: Option<(usize, String)> = None;
+        for line in text.lines() {
+            let header = line.strip_prefix("Part ").and_then(|rest| {
+                let (part, answer) = rest.split_once(':')?;
+                match part.trim() {
+                    "1" => Some((0, answer)),
+                    "2" => Some((1, answer)),
+                    _ => None,
+                }
+            });
+            if let Some((part, answer)) = header {
+                if let Some((prev, expected)) = current.take() {
+                    answers.parts[prev] = Some(normalize(&expected));
+                }
+                current = Some((part, answer.strip_prefix(' ').unwrap_or(answer).to_string()));
+            } else if let Some((_, expected)) = current.as_mut() {
+                expected.push('\n');
+                expected.push_str(line);
+            }
+        }
+        if let Some((prev, expected)) = current {
+            answers.parts[prev] = Some(normalize(&expected));
+        }
+        answers
+    }
+
+    // Load answers from a file, which need not exist.
+    pub fn load(path: &Path) -> Result<Answers, io::Error> {
+        match fs::read_to_string(path) {
+            Ok(text) => Ok(Answers::from(&text)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Answers::default()),
+            Err(error) => Err(error),
+        }
+    }
+
+    // Expected answer for part (1 or 2).
+    pub fn expected(&self, part: usize) -> Option<&str> {
+        self.parts[part - 1].as_deref()
+    }
+
+    pub fn check(&self, part: usize, answer: &str) -> Status {
+        match self.expected(part) {
+            Some(expected) if expected == normalize(answer) => Status::Pass,
+            Some(_) => Status::Fail,
+            None => Status::Unknown,
+        }
+    }
+}
@@ -1,57 +1,354 @@
-use std::num::ParseIntError;
-use std::num::IntErrorKind;
+use std::collections::BTreeSet;
+use std::ops::RangeInclusive;
 use std::path::PathBuf;
+use std::str::FromStr;
 pub use structopt::StructOpt;
 
-fn parse_day_range(s: &str) -> Result<(u8, u8), ParseIntError> {
-    if s.trim().len() == 0 {
-        return Ok((1, 25));
+use advent2021::params::Arg;
+use advent2021::trace::Filter;
+
+use crate::report::Format;
+
+// -i - reads the input from stdin.
+pub const STDIN: &str = "-";
+
+// The last day of the calendar.
+pub const LAST_DAY: u8 = 25;
+
+// A set of days selected on the command line.
+//
+// A comma-separated list of days and inclusive ranges, where a leading ! excludes
+// instead of includes:
+//
+//   3          just day 3
+//   1,3,5..7   days 1, 3, 5, 6, and 7
+//   ..14,!12   days 1 through 14 except 12
+//   !12        every day except 12
+//
+// Either end of a range may be left off to mean the first or last day, and
+// ..= works as well as .. for the same inclusive range.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DaySet(BTreeSet<u8>);
+
+impl DaySet {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    // Selected days in order.
+    pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+fn parse_day(s: &str, default: u8) -> Result<u8, String> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Ok(default);
+    }
+    match s.parse::<u8>() {
+        Ok(day) if (1..=LAST_DAY).contains(&day) => Ok(day),
+        _ => Err(format!("'{}' is not a day (1-{})", s, LAST_DAY)),
+    }
+}
+
+// Exactly one day, with no default.
+fn parse_one_day(s: &str) -> Result<u8, String> {
+    match parse_day(s, 0)? {
+        0 => Err(format!("expected a day (1-{})", LAST_DAY)),
+        day => Ok(day),
+    }
+}
+
+fn parse_days(s: &str) -> Result<RangeInclusive<u8>, String> {
+    match s.split_once("..") {
+        None => parse_day(s, 0).and_then(|day| {
+            if day == 0 { Err("missing day".to_string()) } else { Ok(day..=day) }
+        }),
+        Some((first, last)) => {
+            let last = last.strip_prefix('=').unwrap_or(last);
+            let (first, last) = (parse_day(first, 1)?, parse_day(last, LAST_DAY)?);
+            if first > last {
+                return Err(format!("range {}..{} is backwards", first, last));
+            }
+            Ok(first..=last)
+        }
     }
-    let mut split = s.split("..");
-    let lb = match split.next().unwrap().parse::<u8>() {
-        Ok(num) => num,
-        Err(error) => match error.kind() {
-            IntErrorKind::Empty => 1u8,
-            _ => return Err(error),
+}
+
+impl FromStr for DaySet {
+    type Err = String;
+    fn from_str(s: &str) -> Result<DaySet, String> {
+        let items: Vec<&str> = s.split(',').map(str::trim).filter(|item| !item.is_empty()).collect();
+        let mut days = BTreeSet::new();
+        // Only exclusions (or nothing) means start from every day.
+        if items.iter().all(|item| item.starts_with('!')) {
+            days.extend(1..=LAST_DAY);
         }
-    };
-    let ub = match split.next() {
-        Some(ub) => match ub.parse::<u8>() {
-            Ok(num) => num,
-            Err(error) => match error.kind() {
-                IntErrorKind::Empty => 25u8,
-                _ => return Err(error),
+        for item in items {
+            match item.strip_prefix('!') {
+                Some(excluded) => for day in parse_days(excluded)? { days.remove(&day); },
+                None => days.extend(parse_days(item)?),
             }
-        },
-        None     => lb, // no '..' was present, use single range
-    };
-    Ok((lb, ub))
+        }
+        if days.is_empty() {
+            return Err(format!("'{}' selects no days", s));
+        }
+        Ok(DaySet(days))
+    }
+}
+
+fn parse_runs(s: &str) -> Result<usize, String> {
+    match s.trim().parse::<usize>() {
+        Ok(0) => Err("need at least one run".to_string()),
+        Ok(runs) => Ok(runs),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+fn parse_part(s: &str) -> Result<usize, String> {
+    match s.trim() {
+        "1" => Ok(1),
+        "2" => Ok(2),
+        _ => Err(format!("part must be 1 or 2, not '{}'", s)),
+    }
+}
+
+#[derive(Debug, Clone, StructOpt)]
+pub enum Command {
+    /// Guess which day(s) an input file is for.
+    ///
+    /// Tries every day's parser on the file and ranks the days by how many lines parse
+    /// cleanly.
+    Identify {
+        /// Input file to identify.
+        file: PathBuf,
+    },
+    /// Serve the solutions over HTTP.
+    ///
+    /// POST an input to /day/N to get back both answers and the parse and part timings as
+    /// JSON, in the form of --format json; a malformed input gets its parse problems back.
+    /// Query parameters set day parameters, like /day/6?days=256.
+    Serve {
+        /// Port to listen on.
+        #[structopt(long, default_value="8021")]
+        port: u16,
+        /// Address to listen on; 0.0.0.0 to accept requests from other machines.
+        #[structopt(long, default_value="127.0.0.1")]
+        address: String,
+        /// Requests to handle at once.
+        #[structopt(long, default_value="4")]
+        workers: usize,
+        /// Largest input to accept, in bytes.
+        #[structopt(long, name="BYTES", default_value="1048576")]
+        max_body: usize,
+    },
+    /// Show how answers and times changed across runs.
+    ///
+    /// Reads the history every run appends to (see --history). Each line covers consecutive
+    /// runs of a part with the same revision, input, and answer; CHANGED marks an answer
+    /// which differs from an earlier one for the same input.
+    History {
+        /// Only show this day.
+        #[structopt(parse(try_from_str=parse_one_day))]
+        day: Option<u8>,
+    },
+    /// Start a new day from a template.
+    ///
+    /// Creates src/dNN/mod.rs with stubs for parse, part1, and part2 and an empty examples
+    /// table, registered with #[advent_day(N)], plus the directory for its input.
+    NewDay {
+        /// Day to create (1-25).
+        #[structopt(parse(try_from_str=parse_one_day))]
+        day: u8,
+        /// Source directory to create the day in, usually src in the repository.
+        #[structopt(long, name="DIR", default_value="src")]
+        src: PathBuf,
+    },
 }
 
-#[derive(Debug, StructOpt)]
+#[derive(Debug, Clone, StructOpt)]
 #[structopt(name="advent2021", about="Solutions for Advent of Code 2021 in Rust.")]
 pub struct Cli {
     /// Display runtime of day(s).
     #[structopt(short, long)]
     pub time: bool,
     /// Force grab input from web again.
+    ///
+    /// Inputs are otherwise only downloaded when missing. The session token is read from
+    /// $AOC_SESSION or ~/.config/advent2021/session.
     #[structopt(short, long)]
     pub web: bool,
-    /// Use alternate input file
+    /// Server to download inputs from.
+    #[structopt(long, env="AOC_BASE_URL", default_value=crate::web::DEFAULT_BASE_URL)]
+    pub base_url: String,
+    /// Check answers against answers.txt beside the input.
+    ///
+    /// Each part is reported as PASS, FAIL, or UNKNOWN (no expected answer).
+    /// Exits non-zero if any part fails.
     #[structopt(short, long)]
-    pub input: Option<PathBuf>,
-    /// Verbose output.
+    pub check: bool,
+    /// Run on the puzzle's worked example instead of the input.
+    ///
+    /// Takes the example name, or runs the first example if omitted. With --check,
+    /// answers are checked against the example's expected answers. Parameters given with
+    /// -a override any the example sets.
     #[structopt(short, long)]
-    pub verbose: bool,
-    /// Day(s) to run (1-25).
+    pub example: Option<Option<String>>,
+    /// Use alternate input file, or - for stdin.
+    ///
+    /// Only one day can be run with -i; see --input-dir for more.
+    #[structopt(short, long)]
+    pub input: Option<PathBuf>,
+    /// Directory holding each day's dXX/input.txt and dXX/answers.txt.
+    ///
+    /// Defaults to ~/.local/share/advent2021 (or under $XDG_DATA_HOME) if it exists, else
+    /// the src directory the binary was built from. Inputs built in with the embed-inputs
+    /// feature are used unless this is given.
+    #[structopt(long, name="ROOT", env="AOC_INPUT_ROOT")]
+    pub input_root: Option<PathBuf>,
+    /// Read each day's input from DIR, named by --input-pattern.
     ///
-    /// Format is like slice notation: [start][..][end].
+    /// Unlike -i, works with multiple days. Expected answers for --check are read from
+    /// dayNN.answers.txt beside each input.
+    #[structopt(long, name="DIR", conflicts_with="input")]
+    pub input_dir: Option<PathBuf>,
+    /// File name of each day's input in --input-dir.
     ///
-    /// If a single number is given, only runs the given day. Otherwise
-    /// if dots are present, either enpoint is clamped to 1..25 when omitted.
-    #[structopt(default_value="1..25", parse(try_from_str=parse_day_range))]
-    pub day: (u8, u8),
-    /// Extra day-specific arguments.
+    /// {NN} is replaced with the zero-padded day and {N} with the plain day.
+    #[structopt(long, default_value="day{NN}.txt")]
+    pub input_pattern: String,
+    /// Trace what the solutions do, as LEVEL or DAY=LEVEL (-v alone is summary).
+    ///
+    /// Levels are off, summary, step, and detail, each including the ones before it. detail
+    /// can be megabytes on real inputs. DAY=LEVEL applies to just that day, and later
+    /// filters take precedence: -v=summary -v=12=detail. The = is required, so a bare -v
+    /// can go anywhere, even before the day(s) to run.
+    #[structopt(short, long, name="FILTER", require_equals = true)]
+    pub verbose: Option<Vec<Filter>>,
+    /// Write trace output to FILE instead of the terminal.
+    #[structopt(long, name="FILE")]
+    pub trace_file: Option<PathBuf>,
+    /// Day(s) to run (1-25).
+    ///
+    /// A comma-separated list of days and inclusive ranges, like 1,3,5..7. Either end of a
+    /// range may be omitted, so ..14 is days 1 through 14. Prefix a day or range with ! to
+    /// exclude it: ..14,!12 is days 1 through 14 except 12, and !12 alone is every day
+    /// except 12. Days which aren't implemented yet are reported as such.
+    #[structopt(default_value="1..25")]
+    pub day: DaySet,
+    /// Day-specific parameters, as NAME=VALUE (listed below).
     #[structopt(short, long)]
-    pub args: Option<Vec<String>>,
+    pub args: Vec<Arg>,
+    /// Output format: text, json, or csv.
+    ///
+    /// json prints one object per day per line, and csv prints a header then one row per day.
+    /// Times are in nanoseconds. Verbose output goes to stderr for json and csv.
+    #[structopt(short, long, default_value="text")]
+    pub format: Format,
+    /// Only run one part (1 or 2); the other is skipped.
+    #[structopt(short, long, parse(try_from_str=parse_part))]
+    pub part: Option<usize>,
+    /// Run up to N days at once (0 for one per CPU).
+    ///
+    /// Results are still printed in day order. With -t, the total runtime shows both
+    /// wall-clock time and the sum of the per-day times.
+    #[structopt(short, long, default_value="1")]
+    pub jobs: usize,
+    /// Only parse the input, reporting every problem found with it.
+    ///
+    /// Exits non-zero if any input has problems.
+    #[structopt(long)]
+    pub validate: bool,
+    /// Benchmark: time parse and each part N times after a warm-up run.
+    ///
+    /// Reports min, median, mean, and standard deviation per phase. The input is read once
+    /// and kept in memory, and the timing table and json/csv times show the median.
+    #[structopt(short, long, parse(try_from_str=parse_runs))]
+    pub bench: Option<usize>,
+    /// Save this run's times as baseline NAME, in baselines/NAME.json.
+    ///
+    /// Days already in the baseline but not run are kept. Combine with --bench for steadier
+    /// numbers.
+    #[structopt(long, name="NAME")]
+    pub save_baseline: Option<String>,
+    /// Compare times against baseline NAME, flagging phases which got slower.
+    ///
+    /// Exits non-zero if any phase is slower than --threshold allows.
+    #[structopt(long, name="BASELINE")]
+    pub compare: Option<String>,
+    /// Percent slower than the baseline before --compare flags a phase.
+    #[structopt(long, default_value="10")]
+    pub threshold: f64,
+    /// Run again whenever an input file changes, showing which answers changed.
+    ///
+    /// Polls the selected days' input files (as given by -i, --input-dir, or the default
+    /// location) and clears the screen before each run. Stop with Ctrl-C.
+    #[structopt(long, conflicts_with="example")]
+    pub watch: bool,
+    /// Answer parts from the cache when the input and code are unchanged.
+    ///
+    /// Answers are cached in ~/.cache/advent2021/answers (or under $XDG_CACHE_HOME), keyed
+    /// by day, part, input, day parameters, and the source the binary was built from.
+    /// Cached parts are marked as such and report no time. Examples aren't cached.
+    #[structopt(long, conflicts_with_all=&["bench", "NAME", "BASELINE"])]
+    pub cache: bool,
+    /// Solve every part again, refreshing the cache.
+    #[structopt(long, conflicts_with="cache")]
+    pub no_cache: bool,
+    /// Record each part's answer and time in FILE, as JSON lines.
+    ///
+    /// Defaults to ~/.local/state/advent2021/history.jsonl (or under $XDG_STATE_HOME).
+    /// Only runs on puzzle inputs are recorded, with the input's hash and the git revision
+    /// the binary was built from. See the history subcommand.
+    #[structopt(long, name="HISTORY", env="AOC_HISTORY")]
+    pub history: Option<PathBuf>,
+    /// Don't record this run in the history.
+    #[structopt(long)]
+    pub no_history: bool,
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Cli {
+    // Number of worker threads to run days on.
+    pub fn jobs(&self) -> usize {
+        match self.jobs {
+            0 => std::thread::available_parallelism().map_or(1, |jobs| jobs.get()),
+            jobs => jobs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn days(s: &str) -> Vec<u8> {
+        s.parse::<DaySet>().unwrap().iter().collect()
+    }
+
+    #[test]
+    fn day_ranges_are_inclusive() {
+        assert_eq!(days("3..5"), [3, 4, 5]);
+        assert_eq!(days("3..=5"), [3, 4, 5]);
+        assert_eq!(days("23.."), [23, 24, 25]);
+        assert_eq!(days("..2"), [1, 2]);
+        assert_eq!(days("7"), [7]);
+    }
+
+    #[test]
+    fn day_lists_and_exclusions() {
+        assert_eq!(days("1,3,5..7"), [1, 3, 5, 6, 7]);
+        assert_eq!(days("..5,!2,!4"), [1, 3, 5]);
+        assert_eq!(days("!2..24"), [1, 25]);
+        assert_eq!(days(""), (1..=25).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn bad_days() {
+        for bad in ["0", "26", "5..3", "x", "1..x", "!..", "3,!3"] {
+            assert!(bad.parse::<DaySet>().is_err(), "{:?} should not parse", bad);
+        }
+    }
 }
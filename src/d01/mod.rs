@@ -1,27 +1,54 @@
 use std::io::BufRead;
 use std::error::Error;
 
-use crate::cli;
-use crate::{Day, PartResult};
+use crate::params::Arg;
+use crate::{advent_day, Day, example::Example, parse};
 
-pub fn part1(depths: &Vec<i32>) -> i32 {
+pub fn part1(depths: &[i32]) -> i32 {
     depths.windows(2).filter_map(|w| if w[1] > w[0] { Some(1) } else { None }).sum()
 }
 
-pub fn part2(depths: &Vec<i32>) -> i32 {
+pub fn part2(depths: &[i32]) -> i32 {
     //    d[n] + d[n-1] + d[n-2] > d[n-3] + d[n-2] + d[n-1]
     // => d[n] > d[n-3]
     depths.windows(4).filter_map(|w| if w[3] > w[0] { Some(1) } else { None }).sum()
 }
 
+#[advent_day(1)]
 pub struct Day1;
 
+examples!(Day1 {
+    larger {
+        input: "\
+199
+200
+208
+210
+200
+207
+240
+269
+260
+263
+",
+        args: &[],
+        answers: [Some("7"), Some("5")],
+    },
+});
+
 impl Day for Day1 {
-    fn mod_path(&self) -> &str { file!() }
-    fn run(&self, input: &mut dyn BufRead, _opts: &cli::Cli) -> Result<(PartResult, PartResult), Box<dyn Error>> {
-        let depths = input.lines().map(|s| s.unwrap().parse().unwrap()).collect();
-        Ok((PartResult::from(|| part1(&depths)),
-            PartResult::from(|| part2(&depths))))
+    type Input = Vec<i32>;
+    fn examples(&self) -> &'static [Example] { EXAMPLES }
+    fn parse(&self, input: &mut dyn BufRead, _args: &[Arg]) -> Result<Vec<i32>, Box<dyn Error>> {
+        let depths = parse::collect(parse::lines(input)
+            .map(|line| { let line = line?; line.parse(line.text.trim(), "depth") }))?;
+        Ok(depths)
+    }
+    fn part1(&self, depths: &Vec<i32>, _args: &[Arg]) -> Result<String, Box<dyn Error>> {
+        Ok(part1(depths).to_string())
+    }
+    fn part2(&self, depths: &Vec<i32>, _args: &[Arg]) -> Result<String, Box<dyn Error>> {
+        Ok(part2(depths).to_string())
     }
 }
 
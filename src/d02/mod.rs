@@ -1,61 +1,81 @@
 use std::io::BufRead;
 use std::error::Error;
 
-use crate::cli;
-use crate::{Day, PartResult};
+use crate::params::Arg;
+use crate::{advent_day, Day, example::Example, parse::{self, ParseError}};
 
-enum Instruction {
+pub enum Instruction {
     MoveX(i32),
     MoveY(i32),
 }
 
-fn part1(instructions: &Vec<Instruction>, verbose: bool) -> i32 {
+fn part1(instructions: &[Instruction]) -> i32 {
     // (x, y)
     let pos = instructions.iter().fold((0,0), |pos, instruction| match instruction {
         Instruction::MoveX(val) => (pos.0 + val, pos.1),
         Instruction::MoveY(val) => (pos.0, pos.1 + val),
     });
-    if verbose {
-        println!("{} forward x {} down", pos.0, pos.1);
-    }
+    trace!(Summary, "{} forward x {} down", pos.0, pos.1);
     pos.0 * pos.1
 }
 
-fn part2(instructions: &Vec<Instruction>, verbose: bool) -> i32 {
+fn part2(instructions: &[Instruction]) -> i32 {
     // (x, y, aim)
     let pos = instructions.iter().fold((0, 0, 0), |vec, instruction| match instruction {
         Instruction::MoveX(val) => { (vec.0 + val * vec.2, vec.1 + val, vec.2) }
         Instruction::MoveY(val) => { (vec.0, vec.1, vec.2 + val) },
     });
-    if verbose {
-        println!("{} forward x {} down ({} aim)", pos.0, pos.1, pos.2);
-    }
+    trace!(Summary, "{} forward x {} down ({} aim)", pos.0, pos.1, pos.2);
     pos.0 * pos.1
 }
 
+#[advent_day(2)]
 pub struct Day2;
 
-fn parse_instruction(line: Result<String, std::io::Error>) -> Instruction {
-    let line = line.unwrap();
-    let mut it = line.split(" ");
-    let direction = it.next().unwrap();
-    let value = it.next().unwrap().parse::<i32>().unwrap();
+fn parse_instruction(line: Result<parse::Line, ParseError>) -> Result<Instruction, ParseError> {
+    let line = line?;
+    let (direction, value) = line.split_once(&line.text, " ")?;
+    let value = line.parse::<i32>(value, "distance")?;
     match direction {
-        "forward" => Instruction::MoveX(value),
-        "down" => Instruction::MoveY(value),
-        "up" => Instruction::MoveY(-value),
-        _ => panic!("unexpected direction")
+        "forward" => Ok(Instruction::MoveX(value)),
+        "down" => Ok(Instruction::MoveY(value)),
+        "up" => Ok(Instruction::MoveY(-value)),
+        _ => Err(line.error(direction, "unexpected direction")),
     }
 }
 
+examples!(Day2 {
+    course {
+        input: "\
+forward 5
+down 5
+forward 8
+up 3
+down 8
+forward 2
+",
+        args: &[],
+        answers: [Some("150"), Some("900")],
+    },
+});
+
 impl Day for Day2 {
-    fn mod_path(&self) -> &str { file!() }
-    fn run(&self, input: &mut dyn BufRead, opts: &cli::Cli)
-        -> Result<(PartResult, PartResult), Box<dyn Error>>
+    type Input = Vec<Instruction>;
+    fn examples(&self) -> &'static [Example] { EXAMPLES }
+    fn parse(&self, input: &mut dyn BufRead, _args: &[Arg])
+        -> Result<Vec<Instruction>, Box<dyn Error>>
+    {
+        Ok(parse::collect(parse::lines(input).map(parse_instruction))?)
+    }
+    fn part1(&self, instructions: &Vec<Instruction>, _args: &[Arg])
+        -> Result<String, Box<dyn Error>>
+    {
+        Ok(part1(instructions).to_string())
+    }
+    fn part2(&self, instructions: &Vec<Instruction>, _args: &[Arg])
+        -> Result<String, Box<dyn Error>>
     {
-        let instructions = input.lines().map(parse_instruction).collect();
-        Ok((PartResult::from(|| part1(&instructions, opts.verbose)),
-            PartResult::from(|| part2(&instructions, opts.verbose))))
+        Ok(part2(instructions).to_string())
     }
 }
 
@@ -1,11 +1,11 @@
 use std::io::BufRead;
-use crate::{cli, Day, PartResult};
+use crate::{advent_day, Day, example::Example, params::Arg, parse::{self, ParseError, ParseErrors}};
 use std::error::Error;
-use std::time::Instant;
 
+#[advent_day(3)]
 pub struct Day3;
 
-fn part1(input: &String) -> (u32, u32, usize) {
+fn part1(input: &str) -> (u64, u64, usize) {
     let mut lines = input.lines();
     let first_line = lines.next().unwrap();
     let bit_width = first_line.len();
@@ -16,7 +16,7 @@ fn part1(input: &String) -> (u32, u32, usize) {
             (num_lines + 1,
                 line
                 .as_bytes()
-                .into_iter()
+                .iter()
                 .enumerate()
                 .map(|(index, chr)| sums[index].saturating_add((chr - b'0').into()))
                 .collect()
@@ -30,11 +30,11 @@ fn part1(input: &String) -> (u32, u32, usize) {
     (gamma, epsilon, bit_width)
 }
 
-fn collect_rating<F>(mut valid: Vec<u32>, mut msb: u32, decide: F) -> u32
-    where F: Fn(Vec<u32>, Vec<u32>) -> Vec<u32>
+fn collect_rating<F>(mut valid: Vec<u64>, mut msb: u64, decide: F) -> u64
+    where F: Fn(Vec<u64>, Vec<u64>) -> Vec<u64>
 {
     while msb > 0 && valid.len() > 1 {
-        let (ones, zeros): (Vec<u32>, Vec<u32>) = valid
+        let (ones, zeros): (Vec<u64>, Vec<u64>) = valid
             .into_iter()
             .partition(|x| x & msb == msb);
         valid = decide(ones, zeros);
@@ -44,12 +44,12 @@ fn collect_rating<F>(mut valid: Vec<u32>, mut msb: u32, decide: F) -> u32
     valid[0]
 }
 
-fn part2(input: &String, width: usize) -> (u32, u32) {
-    let input: Vec<u32> = input
+fn part2(input: &str, width: usize) -> (u64, u64) {
+    let input: Vec<u64> = input
         .lines()
-        .map(|line| u32::from_str_radix(line, 2).unwrap())
+        .map(|line| u64::from_str_radix(line, 2).unwrap())
         .collect();
-    let msb: u32 = 1 << (width - 1);
+    let msb: u64 = 1 << (width - 1);
 
     let oxy = collect_rating(input.clone(), msb, |ones, zeros|
         if ones.len() >= zeros.len() { ones } else { zeros });
@@ -60,27 +60,76 @@ fn part2(input: &String, width: usize) -> (u32, u32) {
     (oxy, co2)
 }
 
-impl Day for Day3 {
-    fn mod_path(&self) -> &str { file!() }
-    fn run(&self, input: &mut dyn BufRead, opts: &cli::Cli)
-        -> Result<(PartResult, PartResult), Box<dyn Error>>
-    {
-        let mut string = String::with_capacity(13000);
-        input.read_to_string(&mut string)?;
-        let tick = Instant::now();
-        let (gamma, epsilon, bit_width) = part1(&string);
-        let p1_time = tick.elapsed();
-        if opts.verbose {
-            println!("    gamma = {0} ({0:b}), epsilon = {1} ({1:b})", gamma, epsilon);
-        }
-        let tick = Instant::now();
-        let (oxy, co2) = part2(&string, bit_width);
-        let p2_time = tick.elapsed();
-        if opts.verbose {
-            println!("    oxy rating = {0} ({0:b}), co2 rating = {1} ({1:b})", oxy, co2);
+// Read the report, checking each line is a binary number of the same width.
+fn check_line(line: &parse::Line, width: &mut Option<usize>) -> Result<(), ParseError> {
+    if let Some(bad) = line.invalid_char(|c| c == '0' || c == '1') {
+        return Err(line.error(bad, "expected binary digit"));
+    }
+    match *width {
+        None if line.text.is_empty() || line.text.len() > 32 =>
+            Err(line.error_line("expected 1 to 32 bits")),
+        None => { *width = Some(line.text.len()); Ok(()) }
+        Some(width) if width != line.text.len() =>
+            Err(line.error_line(format!("expected {} bits like the first line", width))),
+        Some(_) => Ok(()),
+    }
+}
+
+fn read_report(input: &mut dyn BufRead) -> Result<String, ParseErrors> {
+    let mut report = String::with_capacity(13000);
+    let mut width = None;
+    let mut errors = ParseErrors::default();
+    for line in parse::lines(input) {
+        let line = line.and_then(|line| check_line(&line, &mut width).map(|_| line));
+        if let Some(line) = errors.check(line) {
+            report.push_str(&line.text);
+            report.push('\n');
         }
-        Ok((PartResult { answer: (gamma * epsilon).to_string(), time: p1_time },
-            PartResult { answer: (oxy * co2).to_string(), time: p2_time }))
     }
+    if report.is_empty() && errors.0.is_empty() {
+        errors.push(ParseError::input("empty report"));
+    }
+    errors.or(report)
 }
 
+examples!(Day3 {
+    report {
+        input: "\
+00100
+11110
+10110
+10111
+10101
+01111
+00111
+11100
+10000
+11001
+00010
+01010
+",
+        args: &[],
+        answers: [Some("198"), Some("230")],
+    },
+});
+
+impl Day for Day3 {
+    type Input = String;
+    fn examples(&self) -> &'static [Example] { EXAMPLES }
+    fn parse(&self, input: &mut dyn BufRead, _args: &[Arg])
+        -> Result<String, Box<dyn Error>>
+    {
+        Ok(read_report(input)?)
+    }
+    fn part1(&self, input: &String, _args: &[Arg]) -> Result<String, Box<dyn Error>> {
+        let (gamma, epsilon, _) = part1(input);
+        trace!(Summary, "    gamma = {0} ({0:b}), epsilon = {1} ({1:b})", gamma, epsilon);
+        Ok((gamma * epsilon).to_string())
+    }
+    fn part2(&self, input: &String, _args: &[Arg]) -> Result<String, Box<dyn Error>> {
+        let bit_width = input.lines().next().ok_or("empty input")?.len();
+        let (oxy, co2) = part2(input, bit_width);
+        trace!(Summary, "    oxy rating = {0} ({0:b}), co2 rating = {1} ({1:b})", oxy, co2);
+        Ok((oxy * co2).to_string())
+    }
+}
@@ -1,22 +1,28 @@
 use std::io::BufRead;
-use crate::{cli, Day, PartResult, util};
+use crate::{advent_day, Day, example::Example, params::Arg, parse::{self, ParseError, ParseErrors}};
 use std::error::Error;
 use std::convert::From;
-use std::time::{Instant, Duration};
 
+#[advent_day(4)]
 pub struct Day4;
 
-fn read_numbers(input: &mut dyn BufRead) -> Result<Vec<u8>, Box<dyn Error>> {
-    let mut number_str = String::new();
-    input.read_line(&mut number_str)?;
-    number_str.pop(); // pop trailing newline
-    let mut numbers = Vec::<u8>::with_capacity(100);
-    for num in number_str.split(",").map(|s| s.parse::<u8>()) {
-        numbers.push(num?);
+const BOARD_SIZE: usize = 5;
+// Numbers are 0-99.
+const MAX_NUMBER: u8 = 99;
+
+fn parse_number(line: &parse::Line, field: &str) -> Result<u8, ParseError> {
+    let value = line.parse::<u8>(field, "number")?;
+    if value > MAX_NUMBER {
+        return Err(line.error(field, format!("number exceeds {}", MAX_NUMBER)));
     }
-    Ok(numbers)
+    Ok(value)
+}
+
+fn read_numbers(line: &parse::Line) -> Result<Vec<u8>, ParseErrors> {
+    parse::collect(line.text.trim_end().split(",").map(|num| parse_number(line, num)))
 }
 
+#[derive(Clone)]
 struct BingoBoard {
     // Board itself -- don't need to actually store this.
     // board: [u8; 25],
@@ -44,20 +50,28 @@ impl BingoBoard {
         }
     }
 
-    pub fn from(string: String) -> Result<BingoBoard, Box<dyn Error>> {
+    pub fn from(rows: &[parse::Line]) -> Result<BingoBoard, ParseErrors> {
         let mut board = BingoBoard::new();
-        // println!("board from: {:?}", board_str);
-        for (row_index, line) in string.lines().enumerate() {
-            for (col_index, value) in line.split_whitespace()
-                    .map(|s| s.parse::<u8>())
-                    .enumerate() {
+        let mut errors = ParseErrors::default();
+        if rows.len() != BOARD_SIZE {
+            errors.push(rows[0].error_line(
+                format!("board has {} rows, expected {}", rows.len(), BOARD_SIZE)));
+        }
+        for (row_index, line) in rows.iter().enumerate() {
+            let values: Vec<&str> = line.text.split_whitespace().collect();
+            if values.len() != BOARD_SIZE {
+                errors.push(line.error_line(
+                    format!("board row has {} numbers, expected {}", values.len(), BOARD_SIZE)));
+            }
+            for (col_index, value) in values.into_iter().enumerate() {
                 // board.board[row_index + col_index * board.row_marks.len()] = value;
-                let value = value?;
-                board.lookup[usize::from(value)] = Some((row_index, col_index));
-                board.sum = board.sum.saturating_add(usize::from(value));
+                if let Some(value) = errors.check(parse_number(line, value)) {
+                    board.lookup[usize::from(value)] = Some((row_index, col_index));
+                    board.sum = board.sum.saturating_add(usize::from(value));
+                }
             }
         }
-        Ok(board)
+        errors.or(board)
     }
 
     // Check for a number on the board.
@@ -97,56 +111,110 @@ impl BingoBoard {
     pub fn sum(&self) -> usize { self.sum }
 }
 
-fn bingo(input: &mut dyn BufRead, verbose: bool) -> Result<(usize, usize), Box<dyn Error>> {
-    let numbers = read_numbers(input)?;
-    let mut first_win_value = 0;
+pub struct Bingo {
+    numbers: Vec<u8>,
+    boards: Vec<BingoBoard>,
+}
+
+// The called numbers on the first line, then boards separated by blank lines.
+fn read_bingo(input: &mut dyn BufRead) -> Result<Bingo, ParseErrors> {
+    let mut lines = parse::lines(input);
+    let first = lines.next().ok_or_else(|| ParseError::input("missing bingo numbers"))??;
+    let mut errors = ParseErrors::default();
+    let numbers = errors.check_all(read_numbers(&first)).unwrap_or_default();
+    let mut boards = Vec::new();
+    let mut rows = Vec::with_capacity(BOARD_SIZE);
+    for line in lines {
+        let line = match errors.check(line) {
+            Some(line) => line,
+            None => continue,
+        };
+        if !line.text.trim().is_empty() {
+            rows.push(line);
+        } else if !rows.is_empty() {
+            boards.extend(errors.check_all(BingoBoard::from(&rows)));
+            rows.clear();
+        }
+    }
+    if !rows.is_empty() {
+        boards.extend(errors.check_all(BingoBoard::from(&rows)));
+    }
+    errors.or(Bingo { numbers, boards })
+}
+
+// Play bingo on fresh copies of the boards.
+//
+// Return the score of the first board to win, or of the last board if !first.
+fn bingo(game: &Bingo, first: bool) -> usize {
+    let numbers = &game.numbers;
+    let mut boards = game.boards.clone();
     let (mut l_value, mut l_number, mut l_index, mut l_board, mut l_sum)
         = (0, 0, 0, 0, 0);
-    if verbose {
-        println!("bingo numbers: {:?}", &numbers);
-    }
-    let mut boards: Vec<BingoBoard> = util::split_groups(input)
-        .map(|s| BingoBoard::from(s).unwrap())
-        .collect();
     for (num_index, num) in numbers.iter().enumerate() {
         for (board_index, board) in boards.iter_mut().enumerate() {
             if let Some(winning_number) = board.check(*num) {
                 l_sum = board.sum();
                 l_number = winning_number.into();
                 l_value = l_sum.saturating_mul(l_number);
-                if verbose {
-                    l_index = num_index;
-                    l_board = board_index;
-                }
-                if first_win_value == 0 {
-                    if verbose {
-                        println!(
-                            "  First win on number {} after {} / {} moves: board {}, sum = {}",
-                            winning_number, num_index + 1, numbers.len(), board_index + 1,
-                            board.sum());
-                    }
-                    first_win_value = l_value;
+                l_index = num_index;
+                l_board = board_index;
+                if first {
+                    trace!(Summary,
+                        "  First win on number {} after {} / {} moves: board {}, sum = {}",
+                        winning_number, num_index + 1, numbers.len(), board_index + 1,
+                        board.sum());
+                    return l_value;
                 }
             }
         }
     }
-    if verbose {
-        println!("  Last win on number {} after {} / {} moves: board {}, sum = {}",
-            l_number, l_index + 1, numbers.len(), l_board + 1, l_sum);
-    }
-    Ok((first_win_value, l_value))
+    trace!(Summary, "  Last win on number {} after {} / {} moves: board {}, sum = {}",
+        l_number, l_index + 1, numbers.len(), l_board + 1, l_sum);
+    l_value
 }
 
+examples!(Day4 {
+    bingo {
+        input: "\
+7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6
+
+14 21 17 24  4
+10 16 15  9 19
+18  8 23 26 20
+22 11 13  6  5
+ 2  0 12  3  7
+",
+        args: &[],
+        answers: [Some("4512"), Some("1924")],
+    },
+});
+
 impl Day for Day4 {
-    fn mod_path(&self) -> &str { file!() }
-    fn run(&self, input: &mut dyn BufRead, opts: &cli::Cli)
-        -> Result<(PartResult, PartResult), Box<dyn Error>>
+    type Input = Bingo;
+    fn examples(&self) -> &'static [Example] { EXAMPLES }
+    fn parse(&self, input: &mut dyn BufRead, _args: &[Arg])
+        -> Result<Bingo, Box<dyn Error>>
     {
-        let time = Instant::now();
-        let (first_win, last_win) = bingo(input, opts.verbose)?;
-        let time = time.elapsed();
-        Ok((PartResult { answer: first_win.to_string(), time },
-            PartResult { answer: last_win.to_string(), time: Duration::new(0, 0) }))
+        let game = read_bingo(input)?;
+        trace!(Step, "bingo numbers: {:?}", &game.numbers);
+        Ok(game)
+    }
+    fn part1(&self, game: &Bingo, _args: &[Arg]) -> Result<String, Box<dyn Error>> {
+        Ok(bingo(game, true).to_string())
+    }
+    fn part2(&self, game: &Bingo, _args: &[Arg]) -> Result<String, Box<dyn Error>> {
+        Ok(bingo(game, false).to_string())
     }
 }
-
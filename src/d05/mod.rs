@@ -1,9 +1,10 @@
 use std::io::BufRead;
-use crate::{cli, Day, PartResult};
+use crate::{advent_day, Day, example::Example, params::Arg, parse::{self, ParseError, ParseErrors}};
 use std::error::Error;
 use std::ops::{Add, AddAssign, Sub, SubAssign};
 use std::cmp::Ordering;
 
+#[advent_day(5)]
 pub struct Day5;
 
 type Coord = u16;
@@ -23,16 +24,18 @@ struct PointDelta(Ordering, Ordering);
 
 impl Point {
     // pub fn new() -> Point { Point { col: 0, row: 0 } }
-    pub fn from(string: String) -> Result<Point, Box<dyn Error>> {
-        let mut coords = string.split(",");
+    // Parse field, an "x,y" slice of line.
+    pub fn from(line: &parse::Line, field: &str) -> Result<Point, ParseError> {
+        let (col, row) = line.split_once(field, ",")?;
+        let (col, row) = (col.trim(), row.trim());
         let point = Point {
-            col: coords.next().ok_or("expected first point coordinate")?.parse()?,
-            row: coords.next().ok_or("expected second point coordinate")?.parse()?,
+            col: line.parse(col, "x coordinate")?,
+            row: line.parse(row, "y coordinate")?,
         };
         if usize::from(point.col) >= INPUT_NCOLS {
-            Err(format!("col {} exceeds ncols {}", point.col, INPUT_NCOLS).into())
+            Err(line.error(col, format!("col exceeds ncols {}", INPUT_NCOLS)))
         } else if usize::from(point.row) >= INPUT_NROWS {
-            Err(format!("row {} exceeds nrows {}", point.row, INPUT_NROWS).into())
+            Err(line.error(row, format!("row exceeds nrows {}", INPUT_NROWS)))
         } else {
             Ok(point)
         }
@@ -119,24 +122,28 @@ impl Iterator for Points {
 }
 
 #[derive(Debug, Clone, Copy)] // , PartialEq, PartialOrd, Eq
-struct Line {
+pub struct Line {
     start: Point,
     end: Point,
 }
 
 impl Line {
     // pub fn new() -> Line { Line { start: Point::new(), end: Point::new() } }
-    pub fn from(string: String) -> Result<Line, Box<dyn Error>> {
-        let mut points = string.split(" -> ");
-        Ok(Line {
-            start: Point::from(points.next().ok_or("expected start point")?.into())?,
-            end: Point::from(points.next().ok_or("expected start point")?.into())?,
-        })
+    pub fn from(line: &parse::Line) -> Result<Line, ParseError> {
+        let (start_text, end_text) = line.split_once(&line.text, " -> ")?;
+        let (start, end) = (Point::from(line, start_text)?, Point::from(line, end_text)?);
+        // Only horizontal, vertical and 45 degree lines can be walked a point at a time.
+        let (cols, rows) = (start.col.max(end.col) - start.col.min(end.col),
+                            start.row.max(end.row) - start.row.min(end.row));
+        if cols != 0 && rows != 0 && cols != rows {
+            return Err(line.error(end_text, "expected a horizontal, vertical or 45 degree line"));
+        }
+        Ok(Line { start, end })
     }
     pub fn is_horiz(&self) -> bool { self.start.row == self.end.row }
     pub fn is_vert(&self) -> bool { self.start.col == self.end.col }
 
-    pub fn points(&self) -> Points {
+    fn points(&self) -> Points {
         let delta = self.end.delta(self.start);
         Points {
             next: self.start,
@@ -146,31 +153,28 @@ impl Line {
     }
 }
 
-fn read_lines(input: &mut dyn BufRead) -> Result<Vec<Line>, Box<dyn Error>> {
+fn read_lines(input: &mut dyn BufRead) -> Result<Vec<Line>, ParseErrors> {
     let mut lines = Vec::<Line>::with_capacity(INPUT_LEN_GUESS);
-    for line in input.lines() {
-        lines.push(Line::from(line?)?);
+    let mut errors = ParseErrors::default();
+    for line in parse::lines(input) {
+        lines.extend(errors.check(line.and_then(|line| Line::from(&line))));
     }
-    Ok(lines)
+    errors.or(lines)
 }
 
 
-fn overlaps<I>(lines: I, verbose: bool) -> usize
+fn overlaps<I>(lines: I) -> usize
     where I: IntoIterator<Item=Line>
 {
     let mut count = 0;
     let mut graph: [u8; INPUT_GRID] = [0; INPUT_GRID];
     for line in lines {
-        if verbose {
-            println!("  line {:?}", line);
-        }
-        for point in line.points().inspect(|p| if verbose { println!("    points {:?}", p); }) {
+        trace!(Step, "  line {:?}", line);
+        for point in line.points().inspect(|p| trace!(Detail, "    points {:?}", p)) {
             let index = usize::from(point.row) * INPUT_NCOLS + usize::from(point.col);
             if graph[index] == 1 {
                 count += 1;
-                if verbose {
-                    println!("  overlap {:?}", point);
-                }
+                trace!(Detail, "  overlap {:?}", point);
             }
             graph[index] = graph[index].saturating_add(1);
         }
@@ -178,21 +182,38 @@ fn overlaps<I>(lines: I, verbose: bool) -> usize
     count
 }
 
+examples!(Day5 {
+    vents {
+        input: "\
+0,9 -> 5,9
+8,0 -> 0,8
+9,4 -> 3,4
+2,2 -> 2,1
+7,0 -> 7,4
+6,4 -> 2,0
+0,9 -> 2,9
+3,4 -> 1,4
+0,0 -> 8,8
+5,5 -> 8,2
+",
+        args: &[],
+        answers: [Some("5"), Some("12")],
+    },
+});
+
 impl Day for Day5 {
-    fn mod_path(&self) -> &str { file!() }
-    fn run(&self, input: &mut dyn BufRead, opts: &cli::Cli)
-        -> Result<(PartResult, PartResult), Box<dyn Error>>
+    type Input = Vec<Line>;
+    fn examples(&self) -> &'static [Example] { EXAMPLES }
+    fn parse(&self, input: &mut dyn BufRead, _args: &[Arg])
+        -> Result<Vec<Line>, Box<dyn Error>>
     {
-        let lines = read_lines(input)?;
-        Ok((
-            PartResult::from(|| {
-                overlaps(lines.iter()
-                        .filter(|l| l.is_horiz() || l.is_vert())
-                        .map(|l| *l),
-                    opts.verbose)
-            }),
-            PartResult::from(|| overlaps(lines.into_iter(), opts.verbose))
-        ))
+        Ok(read_lines(input)?)
+    }
+    fn part1(&self, lines: &Vec<Line>, _args: &[Arg]) -> Result<String, Box<dyn Error>> {
+        Ok(overlaps(lines.iter()
+                .filter(|l| l.is_horiz() || l.is_vert()).copied()).to_string())
+    }
+    fn part2(&self, lines: &Vec<Line>, _args: &[Arg]) -> Result<String, Box<dyn Error>> {
+        Ok(overlaps(lines.iter().copied()).to_string())
     }
 }
-
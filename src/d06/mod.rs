@@ -1,8 +1,9 @@
 use std::io::BufRead;
-use crate::{cli, Day, PartResult, util};
+use crate::{advent_day, Day, example::Example, params::{self, Arg, Kind, Param}, trace::{self, Level}, util};
 use std::error::Error;
 use std::cmp::min;
 
+#[advent_day(6)]
 pub struct Day6;
 
 const SPAWN_PERIOD: usize = 7;
@@ -16,7 +17,7 @@ const RING_SIZE: usize = SPAWN_PERIOD + SPAWN_DELAY;
 // one with T=6, and one with T=8.
 //
 // The return value is the number of fish alive after num_days.
-fn simulate<I>(fish: I, num_days: usize, verbose: bool) -> usize
+fn simulate<I>(fish: I, num_days: usize) -> usize
     where I: IntoIterator<Item=usize>
 {
     let mut num_fish: usize = 0;
@@ -26,14 +27,10 @@ fn simulate<I>(fish: I, num_days: usize, verbose: bool) -> usize
     for t in fish {
         num_fish += 1;
         spawn_ring[t] += 1;
-        if verbose {
-            println!("  {:>8} fish to split on day {}", spawn_ring[t], t);
-        }
+        trace!(Detail, "  {:>8} fish to split on day {}", spawn_ring[t], t);
         day = min(day, t);
     }
-    if verbose {
-        println!("  {:>8} fish to start", num_fish);
-    }
+    trace!(Summary, "  {:>8} fish to start", num_fish);
     let mut day_slot = day; // always day % RING_SIZE
     let mut last_spawned = 0;
     for day in day..num_days+1 /* day zero doesn't count */ {
@@ -48,11 +45,11 @@ fn simulate<I>(fish: I, num_days: usize, verbose: bool) -> usize
         spawn_ring[(day_slot + SPAWN_PERIOD) % RING_SIZE] += last_spawned;
         // delayed spawn always occurs every RING_SIZE, so we're re-using this day slot
         spawn_ring[day_slot /* + SPAWN_PERIOD + SPAWN_DELAY + 1*/] = last_spawned;
-        if verbose {
-            println!("|day {:2}| created {} fish, now {:<8}", day, last_spawned, num_fish);
-            println!(" {:>9} fish to split on day {}",
+        if trace::enabled(Level::Step) {
+            vprintln!("|day {:2}| created {} fish, now {:<8}", day, last_spawned, num_fish);
+            vprintln!(" {:>9} fish to split on day {}",
                  spawn_ring[(day_slot + SPAWN_PERIOD) % RING_SIZE], day + SPAWN_PERIOD);
-            println!(" {:>9} fish to split on day {}",
+            vprintln!(" {:>9} fish to split on day {}",
                  spawn_ring[day_slot], day + RING_SIZE);
         }
         day_slot += 1;
@@ -60,22 +57,45 @@ fn simulate<I>(fish: I, num_days: usize, verbose: bool) -> usize
     num_fish
 }
 
+const PARAMS: &[Param] = &[
+    Param { name: "days", kind: Kind::Count { min: 0, max: 400 }, default: "80",
+            help: "Days to simulate in part 1" },
+];
+
+examples!(Day6 {
+    days80 {
+        input: "3,4,3,1,2\n",
+        args: &[],
+        answers: [Some("5934"), None],
+    },
+    days18 {
+        input: "3,4,3,1,2\n",
+        args: &["days=18"],
+        answers: [Some("26"), None],
+    },
+    days256 {
+        input: "3,4,3,1,2\n",
+        args: &["days=256"],
+        answers: [Some("26984457539"), None],
+    },
+});
+
 impl Day for Day6 {
-    fn mod_path(&self) -> &str { file!() }
-    fn run(&self, input: &mut dyn BufRead, opts: &cli::Cli)
-        -> Result<(PartResult, PartResult), Box<dyn Error>>
+    type Input = Vec<usize>;
+    fn examples(&self) -> &'static [Example] { EXAMPLES }
+    fn params(&self) -> &'static [Param] { PARAMS }
+    fn parse(&self, input: &mut dyn BufRead, _args: &[Arg])
+        -> Result<Vec<usize>, Box<dyn Error>>
     {
-        let mut num_days = 80;
-        if let Some(args) = &opts.args {
-            assert_eq!(args.len() > 0, true);
-            num_days = args[0].parse()?;
-        }
-        if opts.verbose {
-            println!("Simulating {} days", num_days);
-        }
-        let input = util::read_csv(input)?;
-        Ok((PartResult::from(|| simulate(input, num_days, opts.verbose)),
-            PartResult::new()))
+        // Timers run from 0 to 8.
+        Ok(util::read_csv_upto(input, RING_SIZE - 1)?)
+    }
+    fn part1(&self, fish: &Vec<usize>, args: &[Arg]) -> Result<String, Box<dyn Error>> {
+        let num_days = params::get(PARAMS, args, "days")?;
+        trace!(Summary, "Simulating {} days", num_days);
+        Ok(simulate(fish.iter().copied(), num_days).to_string())
+    }
+    fn part2(&self, _fish: &Vec<usize>, _args: &[Arg]) -> Result<String, Box<dyn Error>> {
+        Ok("unimplemented".into())
     }
 }
-
@@ -1,15 +1,16 @@
 use std::io::BufRead;
-use crate::{cli, Day, PartResult, util};
+use crate::{advent_day, Day, example::Example, params::Arg, util};
 use std::error::Error;
 
+#[advent_day(7)]
 pub struct Day7;
 
-fn mean(input: &Vec<usize>) -> usize {
-    return input.iter().sum::<usize>() / input.len();
+fn mean(input: &[usize]) -> usize {
+    input.iter().sum::<usize>() / input.len()
 }
 
 fn distance(a: usize, b: usize) -> usize {
-    if a > b { a - b } else { b - a }
+    a.abs_diff(b)
 }
 
 fn geometric_distance(a: usize, b: usize) -> usize {
@@ -17,34 +18,42 @@ fn geometric_distance(a: usize, b: usize) -> usize {
     (n * (n + 1)) / 2
 }
 
-fn cost<F>(positions: &Vec<usize>, midpoint: usize, cost: F) -> usize
+fn cost<F>(positions: &[usize], midpoint: usize, cost: F) -> usize
     where F: Fn(usize, usize) -> usize
 {
     positions.iter().map(|s| cost(*s, midpoint)).sum()
 }
 
+examples!(Day7 {
+    crabs {
+        input: "16,1,2,0,4,2,7,1,2,14\n",
+        args: &[],
+        answers: [Some("37"), Some("168")],
+    },
+});
+
 impl Day for Day7 {
-    fn mod_path(&self) -> &str { file!() }
-    fn run(&self, input: &mut dyn BufRead, opts: &cli::Cli)
-        -> Result<(PartResult, PartResult), Box<dyn Error>>
+    type Input = Vec<usize>;
+    fn examples(&self) -> &'static [Example] { EXAMPLES }
+    fn parse(&self, input: &mut dyn BufRead, _args: &[Arg])
+        -> Result<Vec<usize>, Box<dyn Error>>
     {
         let mut input = util::read_csv(input)?;
         input.sort();
-        Ok((PartResult::from(|| {
-                let position = *util::median(&input);
-                if opts.verbose {
-                    println!("  optimal linear position is {}", position);
-                }
-                cost(&input, position, distance)
-            }),
-            PartResult::from(|| {
-                let position = mean(&input);
-                if opts.verbose {
-                    println!("  optimal geometric position is {}", position);
-                }
-                cost(&input, position, geometric_distance)
-            }),
-        ))
+        Ok(input)
+    }
+    fn part1(&self, input: &Vec<usize>, _args: &[Arg]) -> Result<String, Box<dyn Error>> {
+        let position = *util::median(input).ok_or("no positions")?;
+        trace!(Summary, "  optimal linear position is {}", position);
+        Ok(cost(input, position, distance).to_string())
+    }
+    fn part2(&self, input: &Vec<usize>, _args: &[Arg]) -> Result<String, Box<dyn Error>> {
+        // The optimum is within 1/2 of the mean, so try both sides of it.
+        let (position, fuel) = (mean(input)..=mean(input) + 1)
+            .map(|position| (position, cost(input, position, geometric_distance)))
+            .min_by_key(|(_, fuel)| *fuel)
+            .unwrap();
+        trace!(Summary, "  optimal geometric position is {}", position);
+        Ok(fuel.to_string())
     }
 }
-
@@ -1,9 +1,10 @@
 use std::io::BufRead;
-use crate::{cli, Day, PartResult};
+use crate::{advent_day, Day, example::Example, params::Arg, parse::{self, ParseError, ParseErrors}, trace::{self, Level}};
 use std::error::Error;
 use std::fmt::{Display, Formatter};
 use std::ops::Add;
 
+#[advent_day(8)]
 pub struct Day8;
 
 const INPUT_LEN_GUESS: usize = 200;
@@ -35,7 +36,7 @@ impl Segment {
     }
 
     fn from_id(id: usize) -> Segment {
-        assert_eq!(id < 7, true);
+        assert!(id < 7);
         Segment(1 << (id * 8))
     }
 
@@ -52,23 +53,23 @@ impl Segment {
         }
     }
 
-    fn to_string(&self) -> String {
+    fn name(&self) -> &'static str {
         match self.0 {
-            A => "a".into(),
-            B => "b".into(),
-            C => "c".into(),
-            D => "d".into(),
-            E => "e".into(),
-            F => "f".into(),
-            G => "g".into(),
-            _ => "?".into(),
+            A => "a",
+            B => "b",
+            C => "c",
+            D => "d",
+            E => "e",
+            F => "f",
+            G => "g",
+            _ => "?",
         }
     }
 }
 
 impl Display for Segment {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        write!(f, "'{}'", self.to_string())
+        write!(f, "'{}'", self.name())
     }
 }
 
@@ -117,10 +118,17 @@ impl Iterator for Segments {
 }
 
 impl Digit {
-    fn from(bytes: &[u8]) -> Result<Digit, Box<dyn Error>> {
+    // Parse word, a slice of line, as the segments of one digit.
+    fn from(line: &parse::Line, word: &str) -> Result<Digit, ParseError> {
         let mut d = 0;
-        for segment in bytes.iter().map(|b| Segment::from(*b)) {
-            d |= segment.ok_or("bad segment character")?.0;
+        for (index, segment) in word.bytes().map(Segment::from).enumerate() {
+            let segment = segment.ok_or_else(|| {
+                line.error(word.get(index..index + 1).unwrap_or(word), "bad segment character")
+            })?;
+            if d & segment.0 != 0 {
+                return Err(line.error(&word[index..index + 1], "repeated segment"));
+            }
+            d |= segment.0;
         }
         Ok(Digit(d))
     }
@@ -133,20 +141,20 @@ impl Digit {
     fn segments(&self) -> Segments {
         Segments { index: 0, digit: self.0 }
     }
-    // Get the value (0-9) this 7-segment digit represents.
-    fn value(&self) -> u8 {
+    // Get the value (0-9) this 7-segment digit represents, if it's a digit.
+    fn value(&self) -> Option<u8> {
         match self.0 {
-            ZERO  => 0,
-            ONE   => 1,
-            TWO   => 2,
-            THREE => 3,
-            FOUR  => 4,
-            FIVE  => 5,
-            SIX   => 6,
-            SEVEN => 7,
-            EIGHT => 8,
-            NINE  => 9,
-            _     => panic!  ("bad value for Digit: {:056b}", self.0),
+            ZERO  => Some(0),
+            ONE   => Some(1),
+            TWO   => Some(2),
+            THREE => Some(3),
+            FOUR  => Some(4),
+            FIVE  => Some(5),
+            SIX   => Some(6),
+            SEVEN => Some(7),
+            EIGHT => Some(8),
+            NINE  => Some(9),
+            _     => None,
         }
     }
 }
@@ -158,13 +166,17 @@ impl Display for Digit {
 }
 
 #[derive(Debug, Clone)]
-struct SegDisplay(Vec<Digit>);
+pub struct SegDisplay(Vec<Digit>);
 
 impl SegDisplay {
-    fn from(line: String) -> Result<SegDisplay, Box<dyn Error>> {
-        let mut vec = Vec::with_capacity(10);
-        for word in line.split(" ") {
-            vec.push(Digit::from(word.trim().as_bytes())?);
+    // Parse part, a slice of line, as space-separated digits.
+    fn from(line: &parse::Line, part: &str, len: usize) -> Result<SegDisplay, ParseError> {
+        let mut vec = Vec::with_capacity(len);
+        for word in part.split_whitespace() {
+            vec.push(Digit::from(line, word)?);
+        }
+        if vec.len() != len {
+            return Err(line.error(part, format!("expected {} digits, found {}", len, vec.len())));
         }
         Ok(SegDisplay(vec))
     }
@@ -181,22 +193,57 @@ impl Display for SegDisplay {
     }
 }
 
+// Check the ten patterns, a slice of line, could be the digits 0-9: all different,
+// with one each of 2, 3, 4 and 7 segments and three each of 5 and 6.
+fn check_patterns(line: &parse::Line, part: &str, patterns: &SegDisplay) -> Result<(), ParseError> {
+    for (index, word) in part.split_whitespace().enumerate() {
+        if patterns.0[..index].iter().any(|earlier| earlier.0 == patterns.0[index].0) {
+            return Err(line.error(word, "repeats an earlier pattern"));
+        }
+    }
+    let mut lengths = [0; 8];
+    for digit in &patterns.0 {
+        lengths[digit.0.count_ones() as usize] += 1;
+    }
+    if lengths != [0, 0, 1, 1, 1, 3, 3, 1] {
+        return Err(line.error(part, "expected patterns with the segment counts of the digits 0-9"));
+    }
+    Ok(())
+}
+
+// Check each output digit, in part of line, is one of the ten patterns.
+fn check_outputs(line: &parse::Line, part: &str, patterns: &SegDisplay, outputs: &SegDisplay)
+    -> Result<(), ParseError>
+{
+    for (word, output) in part.split_whitespace().zip(&outputs.0) {
+        if !patterns.0.iter().any(|pattern| pattern.0 == output.0) {
+            return Err(line.error(word, "not one of the ten patterns"));
+        }
+    }
+    Ok(())
+}
+
 fn read_displays(input: &mut dyn BufRead)
-    -> Result<(Vec<SegDisplay>, Vec<SegDisplay>), Box<dyn Error>>
+    -> Result<(Vec<SegDisplay>, Vec<SegDisplay>), ParseErrors>
 {
     let mut inputs = Vec::<SegDisplay>::with_capacity(INPUT_LEN_GUESS);
     let mut outputs = Vec::<SegDisplay>::with_capacity(INPUT_LEN_GUESS);
-    for line in input.lines() {
-        let line = line?;
-        let mut parts = line.split(" | ");
-        inputs.push(
-            SegDisplay::from(parts.next().ok_or("expected ten digit part")?.into())?
-        );
-        outputs.push(
-            SegDisplay::from(parts.next().ok_or("expected output display part")?.into())?
-        );
+    let mut errors = ParseErrors::default();
+    for line in parse::lines(input) {
+        let display = line.and_then(|line| {
+            let (ten_digits, output) = line.split_once(&line.text, " | ")?;
+            let patterns = SegDisplay::from(&line, ten_digits, 10)?;
+            let outputs = SegDisplay::from(&line, output, 4)?;
+            check_patterns(&line, ten_digits, &patterns)?;
+            check_outputs(&line, output, &patterns, &outputs)?;
+            Ok((patterns, outputs))
+        });
+        if let Some((input, output)) = errors.check(display) {
+            inputs.push(input);
+            outputs.push(output);
+        }
     }
-    Ok((inputs, outputs))
+    errors.or((inputs, outputs))
 }
 
 fn part1(_inputs: &Vec<SegDisplay>, outputs: &Vec<SegDisplay>) -> usize {
@@ -220,8 +267,10 @@ fn part1(_inputs: &Vec<SegDisplay>, outputs: &Vec<SegDisplay>) -> usize {
 // after some careful analysis, the mapping between signal wires and segments makes sense :-)
 //
 // Returns a segment decoder D such that D[s] is the segment for the encoded segment s.
-fn careful_analysis(digits: &SegDisplay, verbose: bool) -> [usize; 7] {
-    assert_eq!(digits.0.len(), 10);
+fn careful_analysis(digits: &SegDisplay) -> Result<[usize; 7], String> {
+    if digits.0.len() != 10 {
+        return Err(format!("expected 10 patterns, found {}", digits.0.len()));
+    }
     // The segment numbering is shown on the left.
     // 0  ---      The mapping of segment number to byte position in the u64 is below.
     // 1 |   | 2    ___ ___ ___ ___ ___ ___ ___ ___ ___
@@ -237,34 +286,28 @@ fn careful_analysis(digits: &SegDisplay, verbose: bool) -> [usize; 7] {
     // Now we have assignments for segment 1, 4, and 5, which uniquely appear 6, 4, and 9 times.
     {
         let mut counts = counts;
-        for index in 0..7 {
+        for (index, codec) in seg_codec.iter_mut().enumerate() {
             match counts & 0xff {
                 6 => {
-                    seg_codec[index] = 1;
-                    if verbose {
-                        println!(
-                           "  segment {} has {} occurrences, so it must be segment {}",
-                           Segment::from_id(index).to_string(), 6, 1,
-                       );
-                    }
+                    *codec = 1;
+                    trace!(Detail,
+                        "  segment {} has {} occurrences, so it must be segment {}",
+                        Segment::from_id(index).name(), 6, 1,
+                    );
                 }, // segment 1 appears in 0,4,5,6,8,9
                 4 =>  {
-                    seg_codec[index] = 4; // segment 4 appears in 0,2,6,8
-                    if verbose {
-                        println!(
-                           "  segment {} has {} occurrences, so it must be segment {}",
-                           Segment::from_id(index).to_string(), 4, 4,
-                       );
-                    }
+                    *codec = 4; // segment 4 appears in 0,2,6,8
+                    trace!(Detail,
+                        "  segment {} has {} occurrences, so it must be segment {}",
+                        Segment::from_id(index).name(), 4, 4,
+                    );
                 },
                 9 => {
-                    seg_codec[index] = 5; // segment 5 appears in 0,1,3,4,5,6,7,8,9
-                    if verbose {
-                        println!(
-                           "  segment {} has {} occurrences, so it must be segment {}",
-                           Segment::from_id(index).to_string(), 9, 5,
-                       );
-                    }
+                    *codec = 5; // segment 5 appears in 0,1,3,4,5,6,7,8,9
+                    trace!(Detail,
+                        "  segment {} has {} occurrences, so it must be segment {}",
+                        Segment::from_id(index).name(), 9, 5,
+                    );
                 },
                 _ => (),
             }
@@ -278,40 +321,32 @@ fn careful_analysis(digits: &SegDisplay, verbose: bool) -> [usize; 7] {
     let counts: u64 = digits.0.iter().filter_map(|digit| {
         match digit.0.count_ones() {
             2 => {
-                if verbose {
-                    println!(
-                        "  digit {} has {} segments, so must be digit {}",
-                        digit, 2, 1,
-                    );
-                }
+                trace!(Detail,
+                    "  digit {} has {} segments, so must be digit {}",
+                    digit, 2, 1,
+                );
                 None
             },
             4 => {
-                if verbose {
-                    println!(
-                        "  digit {} has {} segments, so must be digit {}",
-                        digit, 4, 4,
-                    );
-                }
+                trace!(Detail,
+                    "  digit {} has {} segments, so must be digit {}",
+                    digit, 4, 4,
+                );
                 None
             },
             3 => {
-                if verbose {
-                    println!(
-                        "  digit {} has {} segments, so must be digit {}",
-                        digit, 3, 7,
-                    );
-                }
+                trace!(Detail,
+                    "  digit {} has {} segments, so must be digit {}",
+                    digit, 3, 7,
+                );
                 digit_seven = digit.0;
                 None
             },
             7 => {
-                if verbose {
-                    println!(
-                        "  digit {} has {} segments, so must be digit {}",
-                        digit, 7, 8,
-                    );
-                }
+                trace!(Detail,
+                    "  digit {} has {} segments, so must be digit {}",
+                    digit, 7, 8,
+                );
                 None
             },
             _ => Some(digit.0),
@@ -320,27 +355,23 @@ fn careful_analysis(digits: &SegDisplay, verbose: bool) -> [usize; 7] {
     // Now we know the assignments for segments 2 and 3, which have counts 4 and 5.
     {
         let mut counts = counts;
-        for index in 0..7 {
+        for (index, codec) in seg_codec.iter_mut().enumerate() {
             // Skip segments we already identified (1, 4, 5)
-            if seg_codec[index] == 0 {
+            if *codec == 0 {
                 match counts & 0xff {
                     4 => {
-                        seg_codec[index] = 2; // segment 2 appears in 0,2,3,9 (ignoring 1,4,7,8)
-                        if verbose {
-                            println!(
-                               "  segment {} has {} occurrences, so it must be segment {}",
-                               Segment::from_id(index).to_string(), 4, 2,
-                           );
-                        }
+                        *codec = 2; // segment 2 appears in 0,2,3,9 (ignoring 1,4,7,8)
+                        trace!(Detail,
+                            "  segment {} has {} occurrences, so it must be segment {}",
+                            Segment::from_id(index).name(), 4, 2,
+                        );
                     },
                     5 => {
-                        seg_codec[index] = 3; // segment 3 appears in 2,3,5,6,9 (ignoring 1,4,7,8)
-                        if verbose {
-                            println!(
-                               "  segment {} has {} occurrences, so it must be segment {}",
-                               Segment::from_id(index).to_string(), 5, 3,
-                           );
-                        }
+                        *codec = 3; // segment 3 appears in 2,3,5,6,9 (ignoring 1,4,7,8)
+                        trace!(Detail,
+                            "  segment {} has {} occurrences, so it must be segment {}",
+                            Segment::from_id(index).name(), 5, 3,
+                        );
                     },
                     _ => (),
                 }
@@ -352,76 +383,92 @@ fn careful_analysis(digits: &SegDisplay, verbose: bool) -> [usize; 7] {
     // Segment 0 appears in digit 7 along with 2 and 5, which we've already decoded.
     // The unassigned segment in this position must be segment 0, and the last remaining
     // unassigned segment must be segment 6.
-    assert_ne!(digit_seven, 0);
+    if digit_seven == 0 {
+        return Err("no pattern for the digit 7".to_string());
+    }
     for (index, decoded_seg) in seg_codec.iter().enumerate() {
         let shift = index * 8;
-        if verbose {
+        if trace::enabled(Level::Detail) {
             let seg = Segment::from_id(index);
-            println!("  testing segment {} ({}, {:056b}) (known to be {}) against digit 7 ({:056b})",
+            vprintln!("  testing segment {} ({}, {:056b}) (known to be {}) against digit 7 ({:056b})",
                 seg, index, seg.0, *decoded_seg, digit_seven);
-            println!("  shift and segment are {}, {}", shift, (digit_seven >> shift) & 0xff);
+            vprintln!("  shift and segment are {}, {}", shift, (digit_seven >> shift) & 0xff);
         }
         if *decoded_seg == 0 && ((digit_seven >> shift) & 0xff) == 0 {
             seg_codec[index] = 6;
-            if verbose {
-                println!(
-                   "  unidentified segment {} does not belong to digit 7 ({:056b}) \
-                   , so it must be segment {}",
-                   Segment::from_id(index).to_string(), digit_seven, 6,
-               );
-            }
+            trace!(Detail,
+                "  unidentified segment {} does not belong to digit 7 ({:056b}) \
+                , so it must be segment {}",
+                Segment::from_id(index).name(), digit_seven, 6,
+            );
             break;
         }
         // Don't need to explicitly assign 0 in the codec, because all slots are 0 by default.
         // But we are done once we've found the slot for 6.
     }
-    if verbose {
-        println!("  final segment map: {:?}", seg_codec);
-    }
+    trace!(Detail, "  final segment map: {:?}", seg_codec);
     // Done!
-    seg_codec
+    Ok(seg_codec)
 }
 
-fn unscramble_outputs(inputs: &Vec<SegDisplay>, outputs: &Vec<SegDisplay>, verbose: bool)
-    -> Vec<usize>
+fn unscramble_outputs(inputs: &[SegDisplay], outputs: &[SegDisplay])
+    -> Result<Vec<usize>, String>
 {
     let mut results = Vec::<usize>::with_capacity(outputs.len());
-    for (digits, output) in inputs.iter().zip(outputs.iter()) {
-        let seg_codec = careful_analysis(digits, verbose);
-        results.push(output.0.iter().fold(0, |num, digit| {
+    for (index, (digits, output)) in inputs.iter().zip(outputs.iter()).enumerate() {
+        let seg_codec = careful_analysis(digits).map_err(|e| format!("display {}: {}", index + 1, e))?;
+        let mut num = 0;
+        for (position, digit) in output.0.iter().enumerate() {
             let digit_value = Digit::from_segments(
                 digit.segments().map(|seg| Segment::from_id(seg_codec[seg.id()]))
-            ).value();
-            num * 10 + usize::from(digit_value)
-        }));
-        if verbose {
-            println!("decoded digits {:?}", results[results.len()-1]);
+            ).value().ok_or_else(|| format!("display {}: output digit {} doesn't decode", index + 1, position + 1))?;
+            num = num * 10 + usize::from(digit_value);
         }
+        results.push(num);
+        trace!(Step, "decoded digits {:?}", results[results.len()-1]);
     }
-    results
+    Ok(results)
 }
 
+examples!(Day8 {
+    single {
+        input: "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf\n",
+        args: &[],
+        answers: [Some("0"), Some("5353")],
+    },
+});
+
 impl Day for Day8 {
-    fn mod_path(&self) -> &str { file!() }
-    fn run(&self, input: &mut dyn BufRead, opts: &cli::Cli)
-        -> Result<(PartResult, PartResult), Box<dyn Error>>
+    type Input = (Vec<SegDisplay>, Vec<SegDisplay>);
+    fn examples(&self) -> &'static [Example] { EXAMPLES }
+    fn parse(&self, input: &mut dyn BufRead, _args: &[Arg])
+        -> Result<(Vec<SegDisplay>, Vec<SegDisplay>), Box<dyn Error>>
     {
         let (display_sets, outputs) = read_displays(input)?;
-        if opts.verbose {
-            println!("display sets:");
+        if trace::enabled(Level::Detail) {
+            vprintln!("display sets:");
             for display_set in &display_sets {
-                println!("  {}", display_set);
+                vprintln!("  {}", display_set);
             }
-            println!();
-            println!("outputs:");
+            vprintln!();
+            vprintln!("outputs:");
             for output in &outputs {
-                println!("  {}", output);
+                vprintln!("  {}", output);
             }
         }
-        Ok((PartResult::from(|| part1(&display_sets, &outputs)),
-            PartResult::from(|| unscramble_outputs(&display_sets, &outputs, opts.verbose)
-                                 .into_iter()
-                                 .sum::<usize>())))
+        Ok((display_sets, outputs))
+    }
+    fn part1(&self, (display_sets, outputs): &(Vec<SegDisplay>, Vec<SegDisplay>), _args: &[Arg])
+        -> Result<String, Box<dyn Error>>
+    {
+        Ok(part1(display_sets, outputs).to_string())
+    }
+    fn part2(&self, (display_sets, outputs): &(Vec<SegDisplay>, Vec<SegDisplay>), _args: &[Arg])
+        -> Result<String, Box<dyn Error>>
+    {
+        Ok(unscramble_outputs(display_sets, outputs)?
+            .into_iter()
+            .sum::<usize>()
+            .to_string())
     }
 }
-
@@ -1,8 +1,8 @@
 use std::io::BufRead;
-use crate::{cli, Day, PartResult, util};
+use crate::{advent_day, Day, example::Example, params::Arg, parse::{self, ParseError}, util};
 use std::error::Error;
-use std::time::{Instant, Duration};
 
+#[advent_day(10)]
 pub struct Day10;
 
 fn score_for(mismatch: u8) -> usize {
@@ -29,61 +29,116 @@ fn opener_for(closer: u8) -> u8 {
     }
 }
 
-fn parse(input: &mut dyn BufRead, verbose: bool) -> Result<(usize, usize), Box<dyn Error>> {
-    let mut corrupt_score = 0;
-    let mut autocomplete_scores = Vec::<usize>::with_capacity(90);
-    for line in input.lines() {
-        let mut stack = Vec::<u8>::with_capacity(128);
-        let mut corrupted = false;
-        'line: for ch in line?.into_bytes() {
-            match ch {
-                b'(' | b'[' | b'{' | b'<' => stack.push(ch),
-                b')' | b']' | b'}' | b'>' => {
-                    if let Some(top) = stack.pop() {
-                        // corrupted line, score the bad token
-                        if top != opener_for(ch) {
-                            let this_score = score_for(ch);
-                            corrupt_score += this_score;
-                            if verbose {
-                                println!(
-                                    "Expected '{}', found '{}': worth {} points",
-                                    char::from(opener_for(ch)), char::from(top), this_score
-                                );
-                            }
-                            corrupted = true;
-                            break 'line;
-                        }
-                    } else {
-                        panic!("Unexpected '{}'", char::from(ch));
+// Result of checking the syntax of one line.
+enum Syntax {
+    // Score of the first illegal closer.
+    Corrupted(usize),
+    // Score of the closers needed to complete the line.
+    Incomplete(usize),
+    Complete,
+}
+
+fn check_syntax(line: &str) -> Syntax {
+    let mut stack = Vec::<u8>::with_capacity(128);
+    for ch in line.bytes() {
+        match ch {
+            b'(' | b'[' | b'{' | b'<' => stack.push(ch),
+            b')' | b']' | b'}' | b'>' => {
+                if let Some(top) = stack.pop() {
+                    // corrupted line, score the bad token
+                    if top != opener_for(ch) {
+                        let this_score = score_for(ch);
+                        trace!(Step,
+                            "Expected '{}', found '{}': worth {} points",
+                            char::from(opener_for(ch)), char::from(top), this_score
+                        );
+                        return Syntax::Corrupted(this_score);
                     }
+                } else {
+                    unreachable!("parse rejects unopened '{}'", char::from(ch));
                 }
-                _ => (),
             }
+            _ => (),
         }
-        // incomplete line, autocomplete and score
-        if !corrupted && stack.len() != 0 {
-            let score = stack.iter().rev().fold(0, |score, opener| score * 5 + score_for(*opener));
-            if verbose {
-                println!("score {:>12} for '{:?}'", score,
-                         stack.iter().map(|b| char::from(*b)).collect::<String>());
-            }
-            autocomplete_scores.push(score);
+    }
+    // incomplete line, autocomplete and score
+    if !stack.is_empty() {
+        let score = stack.iter().rev().fold(0, |score, opener| score * 5 + score_for(*opener));
+        trace!(Step, "score {:>12} for '{:?}'", score,
+            stack.iter().map(|b| char::from(*b)).collect::<String>());
+        Syntax::Incomplete(score)
+    } else {
+        Syntax::Complete
+    }
+}
+
+examples!(Day10 {
+    chunks {
+        input: "\
+[({(<(())[]>[[{[]{<()<>>
+[(()[<>])]({[<{<<[]>>(
+{([(<{}[<>[]}>{[]{[(<()>
+(((({<>}<{<{<>}{[]{[]{}
+[[<[([]))<([[{}[[()]]]
+[{[{({}]{}}([{[{{{}}([]
+{<[[]]>}<{[{[{[]{()[[[]
+[<(<(<(<{}))><([]([]()
+<{([([[(<>()){}]>(<<{{
+<{([{{}}[<[[[<>{}]]]>[]]
+",
+        args: &[],
+        answers: [Some("26397"), Some("288957")],
+    },
+});
+
+// Check a line has only brackets, and never closes more than it opened.
+fn read_line(line: parse::Line) -> Result<String, ParseError> {
+    if let Some(bad) = line.invalid_char(|c| "()[]{}<>".contains(c)) {
+        return Err(line.error(bad, "expected bracket"));
+    }
+    let mut depth = 0usize;
+    for (index, ch) in line.text.char_indices() {
+        if "([{<".contains(ch) {
+            depth += 1;
+        } else if depth == 0 {
+            return Err(line.error(&line.text[index..index + 1], "closer with nothing open"));
+        } else {
+            depth -= 1;
         }
     }
-    autocomplete_scores.sort();
-    Ok((corrupt_score, *util::median(&autocomplete_scores)))
+    Ok(line.text)
 }
 
 impl Day for Day10 {
-    fn mod_path(&self) -> &str { file!() }
-    fn run(&self, input: &mut dyn BufRead, opts: &cli::Cli)
-        -> Result<(PartResult, PartResult), Box<dyn Error>>
+    type Input = Vec<String>;
+    fn examples(&self) -> &'static [Example] { EXAMPLES }
+    fn parse(&self, input: &mut dyn BufRead, _args: &[Arg])
+        -> Result<Vec<String>, Box<dyn Error>>
     {
-        let time = Instant::now();
-        let (score1, score2) = parse(input, opts.verbose)?;
-        let time = time.elapsed();
-        Ok((PartResult { answer: score1.to_string(), time: time },
-            PartResult { answer: score2.to_string(), time: Duration::new(0, 0) }))
+        let lines = parse::collect(parse::lines(input).map(|line| read_line(line?)))?;
+        if lines.is_empty() {
+            return Err(ParseError::input("empty input").into());
+        }
+        Ok(lines)
+    }
+    fn part1(&self, lines: &Vec<String>, _args: &[Arg]) -> Result<String, Box<dyn Error>> {
+        let corrupt_score: usize = lines.iter()
+            .filter_map(|line| match check_syntax(line) {
+                Syntax::Corrupted(score) => Some(score),
+                _ => None,
+            })
+            .sum();
+        Ok(corrupt_score.to_string())
+    }
+    fn part2(&self, lines: &Vec<String>, _args: &[Arg]) -> Result<String, Box<dyn Error>> {
+        let mut autocomplete_scores: Vec<usize> = lines.iter()
+            .filter_map(|line| match check_syntax(line) {
+                Syntax::Incomplete(score) => Some(score),
+                _ => None,
+            })
+            .collect();
+        autocomplete_scores.sort();
+        let median = util::median(&autocomplete_scores).ok_or("no incomplete lines")?;
+        Ok(median.to_string())
     }
 }
-
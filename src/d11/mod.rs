@@ -1,7 +1,8 @@
 use std::io::BufRead;
-use crate::{cli, Day, PartResult, util, util::vec2d::Vec2d};
+use crate::{advent_day, Day, example::Example, params::{self, Arg, Kind, Param}, util, util::vec2d::Vec2d};
 use std::error::Error;
 
+#[advent_day(11)]
 pub struct Day11;
 
 fn enumerate_adjacent<T>(vec2d: &Vec2d<T>, (row, col): (usize, usize))
@@ -15,7 +16,7 @@ fn enumerate_adjacent<T>(vec2d: &Vec2d<T>, (row, col): (usize, usize))
 }
 
 // Simulate a step and return the number of flashes.
-fn step(octopi: &mut Vec2d<u8>, verbose: bool) -> usize {
+fn step(octopi: &mut Vec2d<u8>) -> usize {
     let mut flashes = 0;
     let mut to_flash = Vec::<(usize, usize)>::with_capacity(octopi.len());
     for row in 0..octopi.nrows() {
@@ -29,20 +30,14 @@ fn step(octopi: &mut Vec2d<u8>, verbose: bool) -> usize {
         }
     }
     while let Some((row, col)) = to_flash.pop() {
-        if verbose {
-            println!("({}, {}) FLASH", row, col);
-        }
+        trace!(Detail, "({}, {}) FLASH", row, col);
         enumerate_adjacent(octopi, (row, col)).for_each(|(row, col)| {
             if let Some(energy) = octopi.at_mut((row, col)) {
                 if *energy != 0 {
-                    if verbose {
-                        println!("  ({}, {}) bumping {} => {}", row, col, *energy, *energy + 1);
-                    }
+                    trace!(Detail, "  ({}, {}) bumping {} => {}", row, col, *energy, *energy + 1);
                     *energy += 1;
                     if *energy > 9 {
-                        if verbose {
-                            println!("  queueing flash on ({}, {})", row, col);
-                        }
+                        trace!(Detail, "  queueing flash on ({}, {})", row, col);
                         to_flash.push((row, col));
                         *energy = 0;
                         flashes += 1;
@@ -55,44 +50,79 @@ fn step(octopi: &mut Vec2d<u8>, verbose: bool) -> usize {
 }
 
 // Simulate N steps and return the number of flashes.
-fn simulate(octopi: &mut Vec2d<u8>, steps: usize, verbose: bool) -> usize {
-    if verbose {
-        println!("Before any steps:\n{}", octopi);
-    }
+fn simulate(octopi: &mut Vec2d<u8>, steps: usize) -> usize {
+    trace!(Step, "Before any steps:\n{}", octopi);
     (0..steps).map(|stepnum| {
-        let flashes = step(octopi, verbose);
-        if verbose {
-            println!("After step {}:\n{}==> {} flashes\n", stepnum + 1, octopi, flashes);
-        }
+        let flashes = step(octopi);
+        trace!(Step, "After step {}:\n{}==> {} flashes\n", stepnum + 1, octopi, flashes);
         flashes
     }).sum()
 }
 
 // After how many steps do the octopi sync (all flash simultaneously)?
-fn sync(octopi: &mut Vec2d<u8>, verbose: bool) -> usize {
+fn sync(octopi: &mut Vec2d<u8>) -> usize {
     let mut steps = 1;
-    while step(octopi, false) != octopi.len() {
+    while step(octopi) != octopi.len() {
         steps += 1;
     }
-    if verbose {
-        println!("Synchronized after {} steps:\n{}\n", steps, octopi);
-    }
+    trace!(Summary, "Synchronized after {} steps:\n{}\n", steps, octopi);
     steps
 }
 
+const PARAMS: &[Param] = &[
+    Param { name: "steps", kind: Kind::Count { min: 0, max: 1_000_000 }, default: "100",
+            help: "Steps to simulate in part 1" },
+];
+
+examples!(Day11 {
+    steps100 {
+        input: "\
+5483143223
+2745854711
+5264556173
+6141336146
+6357385478
+4167524645
+2176841721
+6882881134
+4846848554
+5283751526
+",
+        args: &[],
+        answers: [Some("1656"), Some("195")],
+    },
+    steps10 {
+        input: "\
+5483143223
+2745854711
+5264556173
+6141336146
+6357385478
+4167524645
+2176841721
+6882881134
+4846848554
+5283751526
+",
+        args: &["steps=10"],
+        answers: [Some("204"), Some("195")],
+    },
+});
+
 impl Day for Day11 {
-    fn mod_path(&self) -> &str { file!() }
-    fn run(&self, input: &mut dyn BufRead, opts: &cli::Cli)
-        -> Result<(PartResult, PartResult), Box<dyn Error>>
+    type Input = Vec2d<u8>;
+    fn examples(&self) -> &'static [Example] { EXAMPLES }
+    fn params(&self) -> &'static [Param] { PARAMS }
+    fn parse(&self, input: &mut dyn BufRead, _args: &[Arg])
+        -> Result<Vec2d<u8>, Box<dyn Error>>
     {
-        let mut octopi = util::read_grid(input)?;
-        let steps = if let Some(steps) = &opts.args {
-            steps.iter().next().ok_or("empty args")?.parse::<usize>()?
-        } else {
-            100
-        };
-        Ok((PartResult::from(|| simulate(&mut octopi, steps, opts.verbose)),
-            PartResult::from(|| sync(&mut octopi, opts.verbose) + steps)))
+        Ok(util::read_grid(input)?)
+    }
+    fn part1(&self, octopi: &Vec2d<u8>, args: &[Arg]) -> Result<String, Box<dyn Error>> {
+        let steps = params::get(PARAMS, args, "steps")?;
+        Ok(simulate(&mut octopi.clone(), steps).to_string())
+    }
+    fn part2(&self, octopi: &Vec2d<u8>, _args: &[Arg]) -> Result<String, Box<dyn Error>> {
+        Ok(sync(&mut octopi.clone()).to_string())
     }
 }
-
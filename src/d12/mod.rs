@@ -1,9 +1,10 @@
 use std::io::BufRead;
-use crate::{cli, Day, PartResult};
+use crate::{advent_day, Day, example::Example, params::Arg, parse::{self, ParseError, ParseErrors}, trace::{self, Level}};
 use std::error::Error;
 use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 
+#[advent_day(12)]
 pub struct Day12;
 
 fn name_trans(node: &str) -> String {
@@ -14,8 +15,21 @@ fn name_trans(node: &str) -> String {
     }
 }
 
+// Check node, a slice of line, is a name name_trans can shorten.
+fn check_node<'a>(line: &parse::Line, node: &'a str) -> Result<&'a str, ParseError> {
+    if node.is_empty() || !node.bytes().all(|b| b.is_ascii_alphabetic()) {
+        Err(line.error(node, "expected cave name"))
+    } else if node.len() > 2 && node != "start" && node != "end" {
+        Err(line.error(node, "cave names must be 1 or 2 letters, or start or end"))
+    } else if node == "rt" || node == "nd" {
+        Err(line.error(node, "cave name is reserved for start or end"))
+    } else {
+        Ok(node)
+    }
+}
+
 #[derive(Debug)]
-struct Graph {
+pub struct Graph {
     adj: HashMap<String, Vec<String>>,
 }
 
@@ -72,7 +86,7 @@ impl Display for Subpath {
 }
 
 fn is_lower(s: &str) -> bool {
-    s.find(char::is_uppercase) == None
+    s.find(char::is_uppercase).is_none()
 }
 
 // Unzipped (node, children) pairs indicating a traversed node and its remaining children.
@@ -128,29 +142,27 @@ struct PathCounter {
     counts: HashMap<Subpath, usize>,
     // Number of times a node can be visited, or None for infinitely many times.
     capacity: HashMap<String, Option<usize>>,
-    verbose: bool,
 }
 
 impl PathCounter {
-    fn with_capacity(c: usize, verbose: bool) -> PathCounter {
+    fn with_capacity(c: usize) -> PathCounter {
         PathCounter {
             stack: Subpath::with_capacity(c),
             status: PathState::with_capacity(c),
             counts: HashMap::with_capacity(c),
             capacity: HashMap::with_capacity(c),
-            verbose,
         }
     }
 
     fn unique_subpath(&self, path: Subpath) -> Subpath {
         let top = path.top();
-        assert_eq!(top.is_none() || self.is_multinode(top.unwrap()), true);
+        assert!(top.is_none() || self.is_multinode(top.unwrap()));
         let mut nodes: Vec<&str> = path.nodes()
             .filter(|node| !self.is_multinode(node))
             .collect();
         nodes.sort();
         Subpath(nodes.into_iter().flat_map(|s| s.chars()).collect::<String>()
-                + &top.unwrap_or(""))
+                + top.unwrap_or(""))
     }
 
     fn get_count(&self, mut path: Subpath) -> Option<usize> {
@@ -160,10 +172,10 @@ impl PathCounter {
             }
         }
         path = self.unique_subpath(path);
-        let result = self.counts.get(&path).and_then(|x| Some(*x));
-        if self.verbose {
+        let result = self.counts.get(&path).copied();
+        if trace::enabled(Level::Detail) {
             if let Some(npaths) = result {
-                println!("  loaded memo for {{{}}} with {} paths", path, npaths);
+                vprintln!("  loaded memo for {{{}}} with {} paths", path, npaths);
             }
         }
         result
@@ -173,9 +185,7 @@ impl PathCounter {
         if let Some(top) = path.top() {
             if self.is_multinode(top) {
                 path = self.unique_subpath(path);
-                if self.verbose {
-                    println!("  memoizing {{{}}} with {} paths", path, count);
-                }
+                trace!(Detail, "  memoizing {{{}}} with {} paths", path, count);
                 *self.counts.entry(path).or_insert(count) = count;
             }
         }
@@ -184,9 +194,7 @@ impl PathCounter {
     fn push(&mut self, g: &Graph, node: &str, end: &str) {
         // Push children onto the stack, and push the node and number
         // of children onto the status.
-        if self.verbose {
-            println!("pushing '{}' with children:", node);
-        }
+        trace!(Detail, "pushing '{}' with children:", node);
         if node == end {
             self.status.push((node, 0, 1));
         } else {
@@ -205,8 +213,8 @@ impl PathCounter {
                             || (capacity.is_some()
                                 && self.status.path.count(adj) < capacity.unwrap())
                         {
-                            if self.verbose {
-                                print!("  '{}'", adj);
+                            if trace::enabled(Level::Detail) {
+                                vprint!("  '{}'", adj);
                             }
                             self.stack.push(adj);
                             children += 1;
@@ -214,9 +222,7 @@ impl PathCounter {
                     }
                     0
                 };
-            if self.verbose {
-                println!("  | count = {}", children);
-            }
+            trace!(Detail, "  | count = {}", children);
             self.status.push((node, children, npaths));
         }
     }
@@ -237,9 +243,9 @@ impl PathCounter {
         self.push(g, start, end);
         while let Some(node) = self.stack.pop() {
             self.push(g, &node, end);
-            if self.verbose {
-                println!("  STACK: {:?} | {}", self.stack, node);
-                println!(" STATUS: {:?}", self.status);
+            if trace::enabled(Level::Detail) {
+                vprintln!("  STACK: {:?} | {}", self.stack, node);
+                vprintln!(" STATUS: {:?}", self.status);
             }
             let top = self.status.top().expect("empty status right after push");
             // If we're looking at a node with no children, we've reached the end of a path.
@@ -258,20 +264,18 @@ impl PathCounter {
                         let subpath = self.status.path.clone();
                         count = self.status.pop().unwrap().2;
                         // We can only memoize paths ending in a multi-node.
-                        if self.verbose {
-                            println!(
-                                "finished subtree '{}' with {} paths",
-                                self.status.path.0, count
-                            );
-                        }
+                        trace!(Detail,
+                            "finished subtree '{}' with {} paths",
+                            self.status.path.0, count
+                        );
                         if self.is_multinode(subpath.top().unwrap()) {
                             self.set_count(subpath, count);
                         }
                     } else {
                         *self.status.child_counts.last_mut().unwrap() -= 1;
-                        if self.verbose {
+                        if trace::enabled(Level::Detail) {
                             let top = self.status.top().unwrap();
-                            println!("now '{}' has {} children left", top.0, top.1);
+                            vprintln!("now '{}' has {} children left", top.0, top.1);
                         }
                         break 'popping;
                     }
@@ -313,21 +317,31 @@ impl PathCounter {
 }
 
 impl Graph {
-    fn from(input: &mut dyn BufRead) -> Result<Graph, Box<dyn Error>> {
+    fn from(input: &mut dyn BufRead) -> Result<Graph, ParseErrors> {
         let mut adj = HashMap::<String, Vec<String>>::new();
-        for line in input.lines() {
-            let line = line?;
-            let mut items = line.split("-");
-            let node1 = name_trans(items.next().ok_or("missing left node")?);
-            let node2 = name_trans(items.next().ok_or("missing right node")?);
+        let mut errors = ParseErrors::default();
+        for line in parse::lines(input) {
+            let edge = line.and_then(|line| {
+                let (node1, node2) = line.split_once(&line.text, "-")?;
+                Ok((name_trans(check_node(&line, node1)?), name_trans(check_node(&line, node2)?)))
+            });
+            let (node1, node2) = match errors.check(edge) {
+                Some(edge) => edge,
+                None => continue,
+            };
             adj.entry(node1.to_string())
-                .or_insert_with(|| Vec::<String>::new())
+                .or_default()
                 .push(node2.to_string());
-            adj.entry(node2.into())
-                .or_insert_with(|| Vec::new())
-                .push(node1.into())
+            adj.entry(node2)
+                .or_default()
+                .push(node1)
         }
-        Ok(Graph { adj })
+        for node in ["start", "end"] {
+            if !adj.contains_key(&name_trans(node)) {
+                errors.push(ParseError::input(format!("no '{}' cave", node)));
+            }
+        }
+        errors.or(Graph { adj })
     }
 
     fn adjacent(&self, node: &str) -> &Vec<String> {
@@ -339,25 +353,24 @@ impl Graph {
     // None means unbounded number of visits.
     fn capacities(&self, start: &str, end: &str) -> HashMap<String, Option<usize>> {
         self.adj.keys().map(|node| {
-            if node == start || node == end { (node.clone(), Some(1)) }
-            else if is_lower(node) { (node.clone(), Some(1)) }
+            if node == start || node == end || is_lower(node) { (node.clone(), Some(1)) }
             else { (node.clone(), None) }
         }).collect()
     }
 
-    fn count_paths(&self, start: &str, end: &str, verbose: bool) -> usize {
+    fn count_paths(&self, start: &str, end: &str) -> usize {
         let start = name_trans(start);
         let end = name_trans(end);
-        let mut counter = PathCounter::with_capacity(self.adj.len(), verbose);
+        let mut counter = PathCounter::with_capacity(self.adj.len());
         counter.capacity = self.capacities(&start, &end);
         counter.count(self, &start, &end)
     }
 
     // Count the number of paths where one lowercase node can be visited twice.
-    fn count_paths_part2(&self, start: &str, end: &str, verbose: bool) -> usize {
+    fn count_paths_part2(&self, start: &str, end: &str) -> usize {
         let start = name_trans(start);
         let end = name_trans(end);
-        let mut counter = PathCounter::with_capacity(self.adj.len(), verbose);
+        let mut counter = PathCounter::with_capacity(self.adj.len());
         let mut paths = HashSet::<String>::new(); // capacity: a lot
         counter.capacity = self.capacities(&start, &end);
         for node in self.adj.keys()
@@ -367,9 +380,7 @@ impl Graph {
                 counter.counts.clear(); // XXX we could maybe save some memos...
                 // XXX this probably counts many paths multiple times, need to de-dup
                 counter.visit(self, &start, &end, |path| {
-                    if verbose {
-                        println!("** visiting {}", path);
-                    }
+                    trace!(Detail, "** visiting {}", path);
                     paths.insert(path.0);
                 });
                 *counter.capacity.get_mut(node).unwrap().as_mut().unwrap() = 1;
@@ -387,23 +398,82 @@ impl Display for Graph {
             for value in values {
                 write!(f, " {:>5}", value)?;
             }
-            write!(f, "\n")?;
+            writeln!(f)?;
         }
         Ok(())
     }
 }
 
+examples!(Day12 {
+    small {
+        input: "\
+start-A
+start-b
+A-c
+A-b
+b-d
+A-end
+b-end
+",
+        args: &[],
+        answers: [Some("10"), Some("36")],
+    },
+    larger {
+        input: "\
+dc-end
+HN-start
+start-kj
+dc-start
+dc-HN
+LN-dc
+HN-end
+kj-sa
+kj-HN
+kj-dc
+",
+        args: &[],
+        answers: [Some("19"), Some("103")],
+    },
+    largest {
+        input: "\
+fs-end
+he-DX
+fs-he
+start-DX
+pj-DX
+end-zg
+zg-sl
+zg-pj
+pj-he
+RW-he
+fs-DX
+pj-RW
+zg-RW
+start-pj
+he-WI
+zg-he
+pj-fs
+start-RW
+",
+        args: &[],
+        answers: [Some("226"), Some("3509")],
+    },
+});
+
 impl Day for Day12 {
-    fn mod_path(&self) -> &str { file!() }
-    fn run(&self, input: &mut dyn BufRead, opts: &cli::Cli)
-        -> Result<(PartResult, PartResult), Box<dyn Error>>
+    type Input = Graph;
+    fn examples(&self) -> &'static [Example] { EXAMPLES }
+    fn parse(&self, input: &mut dyn BufRead, _args: &[Arg])
+        -> Result<Graph, Box<dyn Error>>
     {
         let g = Graph::from(input)?;
-        if opts.verbose {
-            println!("{}", g);
-        }
-        Ok((PartResult::from(|| g.count_paths("start".into(), "end".into(), opts.verbose)),
-            PartResult::from(|| g.count_paths_part2("start".into(), "end".into(), opts.verbose))))
+        trace!(Step, "{}", g);
+        Ok(g)
+    }
+    fn part1(&self, g: &Graph, _args: &[Arg]) -> Result<String, Box<dyn Error>> {
+        Ok(g.count_paths("start", "end").to_string())
+    }
+    fn part2(&self, g: &Graph, _args: &[Arg]) -> Result<String, Box<dyn Error>> {
+        Ok(g.count_paths_part2("start", "end").to_string())
     }
 }
-
@@ -1,52 +1,93 @@
 use std::io::BufRead;
-use crate::{cli, Day, PartResult, util::vec2d::Vec2d};
+use crate::{advent_day, Day, example::Example, params::Arg, parse::{self, ParseError, ParseErrors}, util::vec2d::Vec2d};
 use std::error::Error;
 
+#[advent_day(13)]
 pub struct Day13;
 
 const INPUT_POINTS_GUESS: usize = 1024;
 const INPUT_FOLDS_GUESS: usize = 16;
+// Largest coordinate or fold line accepted, which bounds the grid we draw.
+const MAX_COORD: u16 = 2047;
+
+// Parse field, a coordinate or fold line in line.
+fn coord(line: &parse::Line, field: &str, what: &str) -> Result<u16, ParseError> {
+    let value = line.parse(field, what)?;
+    if value > MAX_COORD {
+        return Err(line.error(field, format!("{} exceeds {}", what, MAX_COORD)));
+    }
+    Ok(value)
+}
 
 #[derive(Debug, Clone, Copy)]
-struct Point(u16, u16);
+pub struct Point(u16, u16);
 
 impl Point {
-    fn from(line: String) -> Result<Point, Box<dyn Error>> {
-        let mut parts = line.splitn(2, ",");
-        Ok(Point(
-            parts.next().ok_or("missing X coord")?.parse()?,
-            parts.next().ok_or("missing Y coord")?.parse()?,
-        ))
+    fn from(line: &parse::Line) -> Result<Point, ParseError> {
+        let (x, y) = line.split_once(&line.text, ",")?;
+        Ok(Point(coord(line, x, "X coord")?, coord(line, y, "Y coord")?))
     }
 }
 
 #[derive(Debug, Clone, Copy)]
-enum Fold {
+pub enum Fold {
     Up(u16),   // fold up over y=...
     Left(u16), // fold left over x=...
 }
 
 impl Fold {
-    fn from(line: String) -> Result<Fold, Box<dyn Error>> {
-        let mut parts = line.splitn(2, "=");
-        match parts.next().ok_or("missing 'fold along'")?
-            .chars().rev().next().ok_or("empty axis")?
-        {
-            'y' => Ok(Fold::Up(parts.next().ok_or("missing fold value")?.parse()?)),
-            'x' => Ok(Fold::Left(parts.next().ok_or("missing fold value")?.parse()?)),
-            axis => Err(format!("invalid fold axis '{}", axis).into()),
+    // fold along y=7
+    fn from(line: &parse::Line) -> Result<Fold, ParseError> {
+        let (along, value) = line.split_once(&line.text, "=")?;
+        let axis = along.strip_prefix("fold along ")
+            .ok_or_else(|| line.error(along, "expected 'fold along x' or 'fold along y'"))?;
+        match axis {
+            "y" => Ok(Fold::Up(coord(line, value, "fold value")?)),
+            "x" => Ok(Fold::Left(coord(line, value, "fold value")?)),
+            _ => Err(line.error(axis, "invalid fold axis")),
         }
     }
 
-    fn fold(&self, point: Point) -> Point {
+    // The fold line and the coordinate of point it folds.
+    fn axis(&self, point: Point) -> (u16, u16) {
         match *self {
-            Fold::Up(y) => Point(point.0, if point.1 > y { y - (point.1 - y) } else { point.1 }),
-            Fold::Left(x) => Point(if point.0 > x { x - (point.0 - x) } else { point.0 }, point.1),
+            Fold::Up(y) => (y, point.1),
+            Fold::Left(x) => (x, point.0),
+        }
+    }
+
+    fn fold(&self, point: Point) -> Result<Point, String> {
+        let (line, at) = self.axis(point);
+        let folded = match at > line {
+            true => line.checked_sub(at - line).ok_or_else(|| format!("{:?} folds {:?} off the grid", self, point))?,
+            false => at,
+        };
+        Ok(match *self {
+            Fold::Up(_) => Point(point.0, folded),
+            Fold::Left(_) => Point(folded, point.1),
+        })
+    }
+
+    // Why folding points here would go wrong, if it would.
+    fn check(&self, points: &[Point]) -> Result<(), String> {
+        let (line, _) = self.axis(Point(0, 0));
+        if line == 0 {
+            return Err("fold at 0 leaves nothing".to_string());
+        }
+        for point in points {
+            let (_, at) = self.axis(*point);
+            if at == line {
+                return Err(format!("point {},{} is on the fold", point.0, point.1));
+            }
+            if u32::from(at) > 2 * u32::from(line) {
+                return Err(format!("point {},{} folds off the grid", point.0, point.1));
+            }
         }
+        Ok(())
     }
 }
 
-fn fold(points: &Vec<Point>, folds: &Vec<Fold>, verbose: bool) -> (Vec2d<char>, usize)
+fn fold(points: &[Point], folds: &[Fold]) -> Result<(Vec2d<char>, usize), Box<dyn Error>>
 {
     // The last fold in each direction indicates the final shape.
     // (Points never overlap the fold lines.)
@@ -73,73 +114,125 @@ fn fold(points: &Vec<Point>, folds: &Vec<Fold>, verbose: bool) -> (Vec2d<char>,
     }
     // Perform all folds on each point.
     // XXX Can we combine all folds into a single affine transformation?
-    if verbose {
-        println!("final shape {:?}", shape);
-    }
+    trace!(Step, "final shape {:?}", shape);
     let mut result = Vec2d::from('.', shape);
     let mut nonzero = 0;
-    for point in points.iter().map(|point| {
-        folds.iter().fold(*point, |point, fold| fold.fold(point))
-    }) {
+    for point in points {
+        let point = folds.iter().try_fold(*point, |point, fold| fold.fold(point))?;
         let index = (usize::from(point.0), usize::from(point.1));
-        if verbose {
-            println!("marking folded point {:?}", point);
+        if index.0 >= shape.0 || index.1 >= shape.1 {
+            return Err(format!("point {:?} is outside the folded grid {:?}", point, shape).into());
         }
+        trace!(Detail, "marking folded point {:?}", point);
         if result[index] == '.' {
             nonzero += 1;
         }
         result[index] = '*';
     }
-    (result, nonzero)
+    Ok((result, nonzero))
 }
 
 fn read_points_folds(input: &mut dyn BufRead)
-    -> Result<(Vec<Point>, Vec<Fold>), Box<dyn Error>>
+    -> Result<(Vec<Point>, Vec<Fold>), ParseErrors>
 {
-    let mut lines = input.lines();
+    let mut lines = parse::lines(input);
     let mut points = Vec::with_capacity(INPUT_POINTS_GUESS);
-    while let Some(line) = lines.next() {
-        let line = line?;
-        if line.len() == 0 {
+    let mut errors = ParseErrors::default();
+    for line in lines.by_ref() {
+        let line = match errors.check(line) {
+            Some(line) => line,
+            None => continue,
+        };
+        if line.text.is_empty() {
             break;
         }
-        points.push(Point::from(line)?);
+        points.extend(errors.check(Point::from(&line)));
+    }
+    if points.is_empty() && errors.0.is_empty() {
+        errors.push(ParseError::input("no points"));
     }
+    // Fold the points as we go, to catch folds which would leave the grid.
+    let mut folded = points.clone();
     let mut folds = Vec::with_capacity(INPUT_FOLDS_GUESS);
-    while let Some(line) = lines.next() {
-        let line = line?;
-        folds.push(Fold::from(line)?);
+    for line in lines {
+        let line = match errors.check(line) {
+            Some(line) => line,
+            None => continue,
+        };
+        let fold = match errors.check(Fold::from(&line)) {
+            Some(fold) => fold,
+            None => continue,
+        };
+        let checked = fold.check(&folded)
+            .and_then(|_| folded.iter().map(|point| fold.fold(*point)).collect::<Result<Vec<Point>, String>>());
+        match checked {
+            Ok(points) => folded = points,
+            Err(message) => errors.push(line.error_line(message)),
+        }
+        folds.push(fold);
     }
-    Ok((points, folds))
+    errors.or((points, folds))
 }
 
+examples!(Day13 {
+    origami {
+        input: "\
+6,10
+0,14
+9,10
+0,3
+10,4
+4,11
+6,0
+6,12
+4,1
+0,13
+10,12
+3,4
+3,0
+8,4
+1,10
+2,14
+8,10
+9,0
+
+fold along y=7
+fold along x=5
+",
+        args: &[],
+        answers: [Some("17"), Some("
+  0 | *  *  *  *  *  .  .
+  1 | *  .  .  .  *  .  .
+  2 | *  .  .  .  *  .  .
+  3 | *  .  .  .  *  .  .
+  4 | *  *  *  *  *  .  .
+")],
+    },
+});
+
 impl Day for Day13 {
-    fn mod_path(&self) -> &str { file!() }
-    fn run(&self, input: &mut dyn BufRead, opts: &cli::Cli)
-        -> Result<(PartResult, PartResult), Box<dyn Error>>
+    type Input = (Vec<Point>, Vec<Fold>);
+    fn examples(&self) -> &'static [Example] { EXAMPLES }
+    fn parse(&self, input: &mut dyn BufRead, _args: &[Arg])
+        -> Result<(Vec<Point>, Vec<Fold>), Box<dyn Error>>
     {
         let (points, folds) = read_points_folds(input)?;
-        if opts.verbose {
-            println!("points:\n{:?}\n\nfolds:\n{:?}\n", points, folds);
-        }
-        Ok((PartResult::from(|| {
-                if opts.verbose {
-                    println!("1 fold:");
-                }
-                let one_fold = folds.iter().take(1).cloned().collect();
-                let (graph, visible) = fold(&points, &one_fold, opts.verbose);
-                if opts.verbose {
-                    println!("\n{}\n", graph);
-                }
-                visible
-            }),
-            PartResult::from(|| {
-                if opts.verbose {
-                    println!("All {} folds:", folds.len());
-                }
-                let (graph, _) = fold(&points, &folds, opts.verbose);
-                format!("\n{}", graph)
-            })))
+        trace!(Detail, "points:\n{:?}\n\nfolds:\n{:?}\n", points, folds);
+        Ok((points, folds))
+    }
+    fn part1(&self, (points, folds): &(Vec<Point>, Vec<Fold>), _args: &[Arg])
+        -> Result<String, Box<dyn Error>>
+    {
+        trace!(Step, "1 fold:");
+        let (graph, visible) = fold(points, &folds[..folds.len().min(1)])?;
+        trace!(Step, "\n{}\n", graph);
+        Ok(visible.to_string())
+    }
+    fn part2(&self, (points, folds): &(Vec<Point>, Vec<Fold>), _args: &[Arg])
+        -> Result<String, Box<dyn Error>>
+    {
+        trace!(Summary, "All {} folds:", folds.len());
+        let (graph, _) = fold(points, folds)?;
+        Ok(format!("\n{}", graph))
     }
 }
-
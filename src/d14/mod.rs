@@ -1,13 +1,17 @@
 use std::io::BufRead;
-use crate::{cli, Day, PartResult};
+use crate::{advent_day, Day, example::Example, params::{self, Arg, Kind, Param}, parse::{self, ParseError, ParseErrors}};
 use std::error::Error;
 use std::collections::HashMap;
 
+#[advent_day(14)]
 pub struct Day14;
 
-// Make sure each element is an uppercase letter.
-fn check_char(b: u8) -> Result<u8, Box<dyn Error>> {
-    if b >= b'A' && b <= b'Z' { Ok(b) } else { Err(format!("bad char '{}'", b).into()) }
+// Make sure each element of field, a slice of line, is an uppercase letter.
+fn check_chars<'a>(line: &parse::Line, field: &'a str) -> Result<&'a [u8], ParseError> {
+    match field.char_indices().find(|(_, c)| !c.is_ascii_uppercase()) {
+        Some((index, c)) => Err(line.error(&field[index..index + c.len_utf8()], "bad element")),
+        None => Ok(field.as_bytes()),
+    }
 }
 
 fn polypair(p1: u8, p2: u8) -> u16 {
@@ -19,26 +23,37 @@ fn pairsplit(p: u16) -> (u8, u8) {
 }
 
 fn read_polymer_rules(input: &mut dyn BufRead)
-    -> Result<(String, HashMap<u16, u8>), Box<dyn Error>>
+    -> Result<(String, HashMap<u16, u8>), ParseErrors>
 {
-    let mut lines = input.lines();
-    let polymer = lines.next().ok_or("missing polymer")??;
-    lines.next().ok_or("missing blank after polymer")??;
+    let mut lines = parse::lines(input);
+    let polymer = lines.next().ok_or_else(|| ParseError::input("missing polymer"))??;
+    let mut errors = ParseErrors::default();
+    if polymer.text.is_empty() {
+        errors.push(polymer.error_line("expected polymer template"));
+    }
+    errors.check(check_chars(&polymer, &polymer.text));
+    let blank = lines.next().ok_or_else(|| ParseError::input("missing blank after polymer"))??;
+    if !blank.text.is_empty() {
+        errors.push(blank.error_line("expected blank line after polymer"));
+    }
     let rules = lines.map(|line| {
         let line = line?;
-        let mut split = line.splitn(2, " -> ").map(|c| c.bytes());
-        let mut pair = split.next().ok_or("missing pair")?.into_iter();
-        let pair: u16 = polypair(
-            check_char(pair.next().ok_or("missing left element of pair")?)?,
-            check_char(pair.next().ok_or("missing right element of pair")?)?);
-        let insertion: u8 = check_char(split.next().ok_or("missing insertion")?
-            .next().ok_or("empty insertion")?)?;
+        let (pair, insertion) = line.split_once(&line.text, " -> ")?;
+        let pair = match check_chars(&line, pair)? {
+            &[left, right] => polypair(left, right),
+            _ => return Err(line.error(pair, "expected pair of 2 elements")),
+        };
+        let insertion = match check_chars(&line, insertion)? {
+            &[insertion] => insertion,
+            _ => return Err(line.error(insertion, "expected 1 element")),
+        };
         Ok((pair, insertion))
-    }).collect::<Result<HashMap<u16, u8>, Box<dyn Error>>>()?;
-    Ok((polymer, rules))
+    });
+    let rules = errors.check_all(parse::collect(rules)).unwrap_or_default();
+    errors.or((polymer.text, rules.into_iter().collect()))
 }
 
-fn dispchars(chars: &Vec<usize>) -> String {
+fn dispchars(chars: &[usize]) -> String {
     let mut s = String::with_capacity(2 + 26 * (2 + 5 + 2));
     s += "[";
     let mut it = chars.iter().enumerate();
@@ -83,7 +98,7 @@ fn disppairs<T>(pairs: &HashMap<u16, T>) -> String
 // Count the numbers of each character after n solution steps.
 //
 // Return the (least, most) frequent counts.
-fn polymer_counts(polymer: &String, rules: &HashMap<u16, u8>, steps: usize, verbose: bool)
+fn polymer_counts(polymer: &String, rules: &HashMap<u16, u8>, steps: usize)
     -> (usize, usize)
 {
     let span = usize::from(1 + b'Z' - b'A');
@@ -101,17 +116,14 @@ fn polymer_counts(polymer: &String, rules: &HashMap<u16, u8>, steps: usize, verb
     fn ichar(b: u8) -> usize { usize::from(b - b'A') }
     for pair in polymer.as_bytes().windows(2) {
         chars[ichar(pair[0])] += 1;
-        chars[ichar(pair[1])] += 1;
         *pairs.entry(polypair(pair[0], pair[1])).or_insert(0) += 1;
     }
     // The last character is not covered by windows(2).
     if let Some(b) = polymer.as_bytes().last() {
         chars[ichar(*b)] += 1;
     }
-    if verbose {
-        println!("To begin, counts are:\n  [chars] {}\n  [pairs] {}",
-                 dispchars(&chars), disppairs(&pairs));
-    }
+    trace!(Step, "To begin, counts are:\n  [chars] {}\n  [pairs] {}",
+        dispchars(&chars), disppairs(&pairs));
     for step in 0..steps {
         // Apply all updates in one step.
         let mut pair_updates: HashMap<u16, isize> = HashMap::with_capacity(span * span);
@@ -135,33 +147,81 @@ fn polymer_counts(polymer: &String, rules: &HashMap<u16, u8>, steps: usize, verb
                 *count -= (-inserted) as usize;
             }
         }
-        if verbose {
-            println!("After step {}:\n  [chars] {}\n  [pairs] {}",
-                     step + 1, dispchars(&chars), disppairs(&pairs));
-        }
+        trace!(Step, "After step {}:\n  [chars] {}\n  [pairs] {}",
+            step + 1, dispchars(&chars), disppairs(&pairs));
     }
     chars.into_iter().fold((usize::MAX, 0), |(min, max), count| {
         (if count > 0 { min.min(count) } else { min }, max.max(count))
     })
 }
 
+const PARAMS: &[Param] = &[
+    Param { name: "steps1", kind: Kind::Count { min: 0, max: 50 }, default: "10",
+            help: "Insertion steps in part 1" },
+    Param { name: "steps2", kind: Kind::Count { min: 0, max: 50 }, default: "40",
+            help: "Insertion steps in part 2" },
+];
+
+examples!(Day14 {
+    polymer {
+        input: "\
+NNCB
+
+CH -> B
+HH -> N
+CB -> H
+NH -> C
+HB -> C
+HC -> B
+HN -> C
+NN -> C
+BH -> H
+NC -> B
+NB -> B
+BN -> B
+BB -> N
+BC -> B
+CC -> N
+CN -> C
+",
+        args: &[],
+        answers: [Some("1588"), Some("2188189693529")],
+    },
+});
+
 impl Day for Day14 {
-    fn mod_path(&self) -> &str { file!() }
-    fn run(&self, input: &mut dyn BufRead, opts: &cli::Cli)
-        -> Result<(PartResult, PartResult), Box<dyn Error>>
+    type Input = (String, HashMap<u16, u8>);
+    fn examples(&self) -> &'static [Example] { EXAMPLES }
+    fn params(&self) -> &'static [Param] { PARAMS }
+    fn parse(&self, input: &mut dyn BufRead, _args: &[Arg])
+        -> Result<(String, HashMap<u16, u8>), Box<dyn Error>>
     {
         let (polymer, rules) = read_polymer_rules(input)?;
-        if opts.verbose {
-            println!("polymer: {}\nrules: {}\n", polymer, disppairs(&rules));
-        }
-        Ok((PartResult::from(|| {
-                let (min, max) = polymer_counts(&polymer, &rules, 10, opts.verbose);
-                max - min
-            }),
-            PartResult::from(|| {
-                let (min, max) = polymer_counts(&polymer, &rules, 40, opts.verbose);
-                max - min
-            })))
+        trace!(Step, "polymer: {}\nrules: {}\n", polymer, disppairs(&rules));
+        Ok((polymer, rules))
+    }
+    fn part1(&self, (polymer, rules): &(String, HashMap<u16, u8>), args: &[Arg])
+        -> Result<String, Box<dyn Error>>
+    {
+        let (min, max) = polymer_counts(polymer, rules, params::get(PARAMS, args, "steps1")?);
+        Ok((max - min).to_string())
+    }
+    fn part2(&self, (polymer, rules): &(String, HashMap<u16, u8>), args: &[Arg])
+        -> Result<String, Box<dyn Error>>
+    {
+        let (min, max) = polymer_counts(polymer, rules, params::get(PARAMS, args, "steps2")?);
+        Ok((max - min).to_string())
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_template() {
+        let errors = read_polymer_rules(&mut "\n\nAB -> C\n".as_bytes()).err().unwrap();
+        assert_eq!(errors.0.len(), 1);
+        assert_eq!((errors.0[0].line, errors.0[0].message.as_str()), (1, "expected polymer template"));
+    }
+}
@@ -0,0 +1,94 @@
+use std::error::Error;
+
+use crate::{params::Arg, Solver};
+
+// A worked example from the puzzle text, with its expected answers.
+pub struct Example {
+    pub name: &'static str,
+    pub input: &'static str,
+    // Day-specific arguments (as with --args) the answers assume.
+    pub args: &'static [&'static str],
+    // Expected answers for part 1 and part 2, if the puzzle gives one.
+    pub answers: [Option<&'static str>; 2],
+}
+
+impl Example {
+    // Expected answers in the answers.txt format understood by check::Answers.
+    pub fn answers_text(&self) -> String {
+        let mut text = String::new();
+        for (part, answer) in self.answers.iter().enumerate() {
+            if let Some(answer) = answer {
+                text += &format!("Part {}: {}\n", part + 1, answer);
+            }
+        }
+        text
+    }
+
+    // The example's arguments, parsed.
+    pub fn args(&self) -> Vec<Arg> {
+        self.args.iter().map(|arg| arg.parse().expect("example args are NAME=VALUE")).collect()
+    }
+}
+
+// Look up an example by name, or the first example if no name is given.
+pub fn find<'a>(day: &'a dyn Solver, name: Option<&str>) -> Result<&'a Example, Box<dyn Error>> {
+    let examples = day.examples();
+    match name {
+        None => examples.first().ok_or_else(|| "no examples for this day".into()),
+        Some(name) => examples.iter().find(|example| example.name == name).ok_or_else(|| {
+            let names: Vec<&str> = examples.iter().map(|example| example.name).collect();
+            format!("no example '{}' (have: {})", name, names.join(", ")).into()
+        }),
+    }
+}
+
+// Declare a day's examples.
+//
+// Defines EXAMPLES for the Day impl to return, plus a #[test] per example
+// checking that the day reproduces the expected answers:
+//
+//   examples!(Day1 {
+//       larger { input: "199\n200\n...", args: &[], answers: [Some("7"), Some("5")] },
+//   });
+//
+macro_rules! examples {
+    ($day:ident {
+        $($name:ident {
+            input: $input:expr,
+            args: $args:expr,
+            answers: $answers:expr $(,)?
+        }),* $(,)?
+    }) => {
+        const EXAMPLES: &[$crate::example::Example] = &[
+            $($crate::example::Example {
+                name: stringify!($name),
+                input: $input,
+                args: $args,
+                answers: $answers,
+            },)*
+        ];
+
+        #[cfg(test)]
+        mod examples {
+            $(
+                #[test]
+                fn $name() {
+                    $crate::example::test(&super::$day, stringify!($name));
+                }
+            )*
+        }
+    };
+}
+
+#[cfg(test)]
+pub fn test(day: &dyn Solver, name: &str) {
+    let example = find(day, Some(name)).unwrap();
+    let result = day.solve(&mut example.input.as_bytes(), &example.args(), None).unwrap();
+    let answers = crate::check::Answers::from(&example.answers_text());
+    for (part, result) in [(1, result.part1.as_ref()), (2, result.part2.as_ref())] {
+        if let (Some(expected), Some(result)) = (answers.expected(part), result) {
+            assert_eq!(answers.check(part, &result.answer), crate::check::Status::Pass,
+                       "part {}: expected {:?}, got {:?}", part, expected, result.answer);
+        }
+    }
+}
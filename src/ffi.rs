@@ -0,0 +1,250 @@
+// C ABI for the solvers, declared in include/advent2021.h.
+//
+// Every call returns normally: errors come back as a status code and
+// message, and panics are caught before they reach the caller.
+
+use std::any::Any;
+use std::convert::TryFrom;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+use std::time::Duration;
+
+use crate::{solve, Answer, Error, DAYS};
+
+pub const ADVENT2021_OK: i32 = 0;
+pub const ADVENT2021_NO_SUCH_DAY: i32 = 1;
+pub const ADVENT2021_NO_SUCH_PART: i32 = 2;
+pub const ADVENT2021_PARAM: i32 = 3;
+pub const ADVENT2021_PARSE: i32 = 4;
+pub const ADVENT2021_SOLVE: i32 = 5;
+pub const ADVENT2021_INVALID_ARGUMENT: i32 = 6;
+pub const ADVENT2021_PANIC: i32 = 7;
+
+#[repr(C)]
+pub struct Advent2021Result {
+    pub status: i32,
+    // The answer on success, else NULL.
+    pub answer: *mut c_char,
+    // What went wrong on failure, else NULL.
+    pub error: *mut c_char,
+    pub parse_ns: u64,
+    pub time_ns: u64,
+}
+
+// Strings from Rust can hold NULs, which C can't; cut them off there.
+fn c_string(s: String) -> *mut c_char {
+    let s = match CString::new(s) {
+        Ok(s) => s,
+        Err(error) => {
+            let end = error.nul_position();
+            CString::new(&error.into_vec()[..end]).expect("cut before the first NUL")
+        }
+    };
+    s.into_raw()
+}
+
+fn nanos(time: Duration) -> u64 {
+    u64::try_from(time.as_nanos()).unwrap_or(u64::MAX)
+}
+
+impl Advent2021Result {
+    fn answer(answer: Answer) -> Advent2021Result {
+        Advent2021Result {
+            status: ADVENT2021_OK,
+            answer: c_string(answer.answer),
+            error: ptr::null_mut(),
+            parse_ns: nanos(answer.parse),
+            time_ns: nanos(answer.time),
+        }
+    }
+
+    fn error(status: i32, message: String) -> Advent2021Result {
+        Advent2021Result {
+            status,
+            answer: ptr::null_mut(),
+            error: c_string(message),
+            parse_ns: 0,
+            time_ns: 0,
+        }
+    }
+}
+
+impl From<Error> for Advent2021Result {
+    fn from(error: Error) -> Advent2021Result {
+        let status = match error {
+            Error::NoSuchDay(_) => ADVENT2021_NO_SUCH_DAY,
+            Error::NoSuchPart(_) => ADVENT2021_NO_SUCH_PART,
+            Error::Param(_) => ADVENT2021_PARAM,
+            Error::Parse(_) => ADVENT2021_PARSE,
+            Error::Solve(_) => ADVENT2021_SOLVE,
+        };
+        Advent2021Result::error(status, error.to_string())
+    }
+}
+
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    match panic.downcast::<String>() {
+        Ok(message) => *message,
+        Err(panic) => match panic.downcast::<&str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "unknown panic".to_string(),
+        },
+    }
+}
+
+// Run f, turning a panic into an ADVENT2021_PANIC result.
+fn catch(f: impl FnOnce() -> Result<Answer, Advent2021Result>) -> *mut Advent2021Result {
+    let result = match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(answer)) => Advent2021Result::answer(answer),
+        Ok(Err(result)) => result,
+        Err(panic) => Advent2021Result::error(ADVENT2021_PANIC, panic_message(panic)),
+    };
+    Box::into_raw(Box::new(result))
+}
+
+fn invalid(message: &str) -> Advent2021Result {
+    Advent2021Result::error(ADVENT2021_INVALID_ARGUMENT, message.to_string())
+}
+
+// Read the NAME=VALUE strings of params.
+unsafe fn read_params(params: *const *const c_char, nparams: usize)
+    -> Result<Vec<(String, String)>, Advent2021Result>
+{
+    if nparams == 0 {
+        return Ok(Vec::new());
+    }
+    if params.is_null() {
+        return Err(invalid("params is NULL"));
+    }
+    let mut pairs = Vec::with_capacity(nparams);
+    for &param in slice::from_raw_parts(params, nparams) {
+        if param.is_null() {
+            return Err(invalid("params holds a NULL"));
+        }
+        let param = CStr::from_ptr(param).to_str().map_err(|_| invalid("param is not UTF-8"))?;
+        let (name, value) = param.split_once('=')
+            .ok_or_else(|| invalid(&format!("expected NAME=VALUE, found '{}'", param)))?;
+        pairs.push((name.to_string(), value.to_string()));
+    }
+    Ok(pairs)
+}
+
+/// Write up to len implemented days into days, returning how many there are.
+///
+/// # Safety
+///
+/// days must be NULL or point to len writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn advent2021_days(days: *mut u8, len: usize) -> usize {
+    let implemented: Vec<u8> = DAYS.iter().enumerate()
+        .filter(|(_, solver)| solver.is_some())
+        .map(|(day_index, _)| (day_index + 1) as u8)
+        .collect();
+    if !days.is_null() {
+        let days = slice::from_raw_parts_mut(days, len);
+        for (day, implemented) in days.iter_mut().zip(&implemented) {
+            *day = *implemented;
+        }
+    }
+    implemented.len()
+}
+
+/// Solve one part of a day for the input_len bytes at input.
+///
+/// params holds nparams NUL-terminated NAME=VALUE strings. Never returns NULL;
+/// free the result with advent2021_free_result.
+///
+/// # Safety
+///
+/// input must point to input_len readable bytes (or be NULL if input_len is 0),
+/// and params to nparams pointers to NUL-terminated strings (or be NULL if
+/// nparams is 0).
+#[no_mangle]
+pub unsafe extern "C" fn advent2021_solve(day: u8, part: u8,
+                                          input: *const u8, input_len: usize,
+                                          params: *const *const c_char, nparams: usize)
+    -> *mut Advent2021Result
+{
+    catch(|| {
+        let input: &[u8] = match (input.is_null(), input_len) {
+            (_, 0) => &[],
+            (true, _) => return Err(invalid("input is NULL")),
+            (false, len) => slice::from_raw_parts(input, len),
+        };
+        let input = std::str::from_utf8(input)
+            .map_err(|e| invalid(&format!("input is not UTF-8 ({})", e)))?;
+        let params = read_params(params, nparams)?;
+        let params: Vec<(&str, &str)> = params.iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        solve(day, part, input, &params).map_err(Advent2021Result::from)
+    })
+}
+
+/// Free a result from advent2021_solve, including its strings.
+///
+/// # Safety
+///
+/// result must be NULL or from advent2021_solve, and not already freed.
+#[no_mangle]
+pub unsafe extern "C" fn advent2021_free_result(result: *mut Advent2021Result) {
+    if result.is_null() {
+        return;
+    }
+    let result = Box::from_raw(result);
+    for s in [result.answer, result.error] {
+        if !s.is_null() {
+            drop(CString::from_raw(s));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DAYS;
+
+    unsafe fn take(result: *mut Advent2021Result) -> (i32, String) {
+        let r = &*result;
+        let text = if r.status == ADVENT2021_OK { r.answer } else { r.error };
+        let text = CStr::from_ptr(text).to_string_lossy().into_owned();
+        let status = r.status;
+        advent2021_free_result(result);
+        (status, text)
+    }
+
+    #[test]
+    fn solve_through_c() {
+        let input = b"3,4,3,1,2\n";
+        let days = CString::new("days=18").unwrap();
+        let params = [days.as_ptr()];
+        unsafe {
+            let result = advent2021_solve(6, 1, input.as_ptr(), input.len(), params.as_ptr(), 1);
+            assert_eq!(take(result), (ADVENT2021_OK, "26".to_string()));
+            let result = advent2021_solve(6, 1, input.as_ptr(), input.len(), ptr::null(), 0);
+            assert_eq!(take(result), (ADVENT2021_OK, "5934".to_string()));
+        }
+    }
+
+    #[test]
+    fn errors_through_c() {
+        unsafe {
+            assert_eq!(take(advent2021_solve(30, 1, ptr::null(), 0, ptr::null(), 0)).0,
+                       ADVENT2021_NO_SUCH_DAY);
+            assert_eq!(take(advent2021_solve(1, 1, ptr::null(), 3, ptr::null(), 0)).0,
+                       ADVENT2021_INVALID_ARGUMENT);
+            let input = b"1\nx\n";
+            assert_eq!(take(advent2021_solve(1, 1, input.as_ptr(), input.len(), ptr::null(), 0)).0,
+                       ADVENT2021_PARSE);
+            let result = catch(|| panic!("boom"));
+            assert_eq!(take(result), (ADVENT2021_PANIC, "boom".to_string()));
+        }
+        let mut days = [0u8; 30];
+        assert_eq!(unsafe { advent2021_days(days.as_mut_ptr(), days.len()) },
+                   DAYS.iter().filter(|day| day.is_some()).count());
+        assert_eq!(days[..3], [1, 2, 3]);
+    }
+}
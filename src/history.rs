@@ -0,0 +1,282 @@
+use std::error::Error;
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_json::{json, Value};
+
+use crate::report::{self, Format, Record, UNIMPLEMENTED};
+use crate::cli;
+
+// The revision this was built from, as git describe --always --dirty, if known.
+const REVISION: Option<&str> = option_env!("ADVENT2021_REVISION");
+
+// One part's answer from one run, one per line of the history file:
+//
+//   {"timestamp": 1760781600, "revision": "5f2566c", "day": 12, "part": 2,
+//    "answer": "93686", "parse_ns": 81234, "time_ns": 40112003, "input": "8c1f03a2d9e4b611"}
+//
+struct Entry {
+    timestamp: u64,
+    revision: Option<String>,
+    day: u8,
+    part: u8,
+    answer: String,
+    time: Duration,
+    input: String,
+}
+
+impl Entry {
+    fn from_json(line: &str) -> Option<Entry> {
+        let value: Value = serde_json::from_str(line).ok()?;
+        Some(Entry {
+            timestamp: value["timestamp"].as_u64()?,
+            revision: value["revision"].as_str().map(String::from),
+            day: value["day"].as_u64()? as u8,
+            part: value["part"].as_u64()? as u8,
+            answer: value["answer"].as_str()?.to_string(),
+            time: Duration::from_nanos(value["time_ns"].as_u64()?),
+            input: value["input"].as_str()?.to_string(),
+        })
+    }
+}
+
+// FNV-1a, which unlike std's hashers is the same from one build to the next.
+pub fn hash(input: &[u8]) -> u64 {
+    input.iter().fold(0xcbf29ce484222325, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3))
+}
+
+// --history, else ~/.local/state/advent2021/history.jsonl, respecting $XDG_STATE_HOME.
+//
+// Not in the data dir: creating that would change where inputs are looked for.
+fn path(opts: &cli::Cli) -> PathBuf {
+    if let Some(path) = &opts.history {
+        return path.clone();
+    }
+    let state = match env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME").unwrap_or_default()).join(".local").join("state"),
+    };
+    state.join("advent2021").join("history.jsonl")
+}
+
+fn nanos(time: Duration) -> u64 {
+    time.as_nanos() as u64
+}
+
+// Append each part answered on a puzzle input to the history.
+//
+// Examples, --validate runs, and days which failed aren't recorded.
+pub fn append(opts: &cli::Cli, records: &[Record]) -> Result<(), Box<dyn Error>> {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let mut lines = String::new();
+    for record in records {
+        let input = match record.input_hash {
+            Some(input) if record.error.is_none() && record.example.is_none() => input,
+            _ => continue,
+        };
+        for (index, part) in record.parts.iter().enumerate() {
+            let part = match part {
+                // A cached answer has no time worth keeping.
+                Some(part) if part.answer != UNIMPLEMENTED && !part.cached => part,
+                _ => continue,
+            };
+            lines += &json!({
+                "timestamp": timestamp,
+                "revision": REVISION,
+                "day": record.day,
+                "part": index + 1,
+                "answer": part.answer,
+                "parse_ns": nanos(record.parse),
+                "time_ns": nanos(part.time),
+                "input": format!("{:016x}", input),
+            }).to_string();
+            lines.push('\n');
+        }
+    }
+    if lines.is_empty() {
+        return Ok(());
+    }
+    let path = path(opts);
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    }
+    OpenOptions::new().create(true).append(true).open(&path)
+        .and_then(|mut file| file.write_all(lines.as_bytes()))
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(())
+}
+
+// Consecutive runs of one part with the same revision, input, and answer.
+struct Span {
+    day: u8,
+    part: u8,
+    first: u64,
+    last: u64,
+    revision: Option<String>,
+    input: String,
+    answer: String,
+    // The answer differs from the last one for the same input.
+    changed: bool,
+    // Sorted.
+    times: Vec<Duration>,
+}
+
+impl Span {
+    fn median(&self) -> Duration {
+        self.times[self.times.len() / 2]
+    }
+}
+
+fn spans(mut entries: Vec<Entry>) -> Vec<Span> {
+    entries.sort_by_key(|entry| (entry.day, entry.part, entry.timestamp));
+    let mut spans: Vec<Span> = Vec::new();
+    for entry in entries {
+        let previous = spans.iter().rev()
+            .take_while(|span| (span.day, span.part) == (entry.day, entry.part))
+            .find(|span| span.input == entry.input);
+        let changed = previous.map_or(false, |span| span.answer != entry.answer);
+        match spans.last_mut() {
+            Some(span) if (span.day, span.part) == (entry.day, entry.part)
+                && span.revision == entry.revision && span.input == entry.input
+                && span.answer == entry.answer => {
+                span.last = entry.timestamp;
+                span.times.push(entry.time);
+            }
+            _ => spans.push(Span {
+                day: entry.day,
+                part: entry.part,
+                first: entry.timestamp,
+                last: entry.timestamp,
+                revision: entry.revision,
+                input: entry.input,
+                answer: entry.answer,
+                changed,
+                times: vec![entry.time],
+            }),
+        }
+    }
+    for span in spans.iter_mut() {
+        span.times.sort();
+    }
+    spans
+}
+
+// Seconds since the epoch as YYYY-MM-DD HH:MM in UTC.
+fn date_time(timestamp: u64) -> String {
+    // Days to a civil date, from Howard Hinnant's date algorithms.
+    let days = (timestamp / 86400) as i64 + 719468;
+    let era = days / 146097;
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    let seconds = timestamp % 86400;
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, seconds / 3600, seconds % 3600 / 60)
+}
+
+fn print_text(spans: &[Span]) {
+    let mut current = None;
+    for span in spans {
+        if current != Some((span.day, span.part)) {
+            if current.is_some() {
+                println!();
+            }
+            println!("Day {} part {}:", span.day, span.part);
+            current = Some((span.day, span.part));
+        }
+        println!("  {}  {:<18} input {}  {:>4} run(s)  median {:<12?} best {:<12?} {}{}",
+                 date_time(span.first), span.revision.as_deref().unwrap_or("unknown"), span.input.get(..8).unwrap_or(&span.input),
+                 span.times.len(), span.median(), span.times[0],
+                 if span.changed { "CHANGED " } else { "" }, span.answer);
+    }
+}
+
+// Show how each part's answers and times changed across runs, optionally for one day.
+pub fn run(day: Option<u8>, opts: &cli::Cli) -> Result<(), Box<dyn Error>> {
+    let path = path(opts);
+    let file = fs::File::open(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut entries = Vec::new();
+    let mut malformed = 0;
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| format!("{}: {}", path.display(), e))?;
+        match Entry::from_json(&line) {
+            Some(entry) if day.map_or(true, |day| day == entry.day) => entries.push(entry),
+            Some(_) => (),
+            None => malformed += 1,
+        }
+    }
+    if malformed > 0 {
+        eprintln!("WARNING: {}: skipped {} malformed line(s)", path.display(), malformed);
+    }
+    let spans = spans(entries);
+    match opts.format {
+        Format::Text if spans.is_empty() => match day {
+            Some(day) => println!("No history for day {} in {}", day, path.display()),
+            None => println!("No history in {}", path.display()),
+        },
+        Format::Text => print_text(&spans),
+        Format::Json => for span in &spans {
+            println!("{}", json!({
+                "day": span.day,
+                "part": span.part,
+                "first": span.first,
+                "last": span.last,
+                "revision": span.revision,
+                "input": span.input,
+                "answer": span.answer,
+                "changed": span.changed,
+                "runs": span.times.len(),
+                "median_ns": nanos(span.median()),
+                "best_ns": nanos(span.times[0]),
+            }));
+        },
+        Format::Csv => {
+            println!("day,part,first,last,revision,input,answer,changed,runs,median_ns,best_ns");
+            for span in &spans {
+                println!("{},{},{},{},{},{},{},{},{},{},{}", span.day, span.part, span.first, span.last,
+                         span.revision.as_deref().unwrap_or_default(), span.input, report::csv_field(&span.answer),
+                         span.changed, span.times.len(), nanos(span.median()), nanos(span.times[0]));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(timestamp: u64, revision: &str, answer: &str) -> Entry {
+        Entry {
+            timestamp,
+            revision: Some(revision.to_string()),
+            day: 12,
+            part: 2,
+            answer: answer.to_string(),
+            time: Duration::from_millis(timestamp),
+            input: "8c1f03a2d9e4b611".to_string(),
+        }
+    }
+
+    #[test]
+    fn answer_changes_are_flagged() {
+        let spans = spans(vec![entry(3, "b", "94"), entry(1, "a", "93"), entry(2, "a", "93"), entry(4, "c", "94")]);
+        let summary: Vec<(&str, usize, bool)> = spans.iter()
+            .map(|span| (span.revision.as_deref().unwrap(), span.times.len(), span.changed))
+            .collect();
+        assert_eq!(summary, [("a", 2, false), ("b", 1, true), ("c", 1, false)]);
+    }
+
+    #[test]
+    fn dates_are_utc() {
+        assert_eq!(date_time(0), "1970-01-01 00:00");
+        assert_eq!(date_time(951782400 + 3660), "2000-02-29 01:01");
+        assert_eq!(date_time(1638316800), "2021-12-01 00:00");
+    }
+}
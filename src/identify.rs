@@ -0,0 +1,98 @@
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use serde_json::json;
+
+use advent2021::{parse, Solver, DAYS};
+
+use crate::cli;
+use crate::report::Format;
+
+// How well an input parsed as one day's input.
+struct Candidate {
+    day: usize,
+    // Lines with at least one problem.
+    bad_lines: usize,
+    lines: usize,
+    // First problem, if any.
+    problem: Option<String>,
+}
+
+impl Candidate {
+    // Fraction of lines which parsed cleanly; 0 if the input as a whole was rejected.
+    fn score(&self) -> f64 {
+        if self.lines == 0 {
+            0.0
+        } else {
+            (self.lines - self.bad_lines.min(self.lines)) as f64 / self.lines as f64
+        }
+    }
+}
+
+fn try_day(day: usize, solver: &dyn Solver, input: &[u8], lines: usize)
+    -> Result<Candidate, Box<dyn Error>>
+{
+    let mut candidate = Candidate { day, bad_lines: 0, lines, problem: None };
+    if let Err(error) = solver.parse(&mut &input[..], &[]) {
+        let problems = parse::errors(error)?;
+        let bad_lines: BTreeSet<usize> = problems.iter().map(|problem| problem.line).collect();
+        // A problem with no line is a problem with the whole input.
+        candidate.bad_lines = if bad_lines.contains(&0) { lines } else { bad_lines.len() };
+        candidate.problem = problems.first().map(|problem| problem.to_string());
+    }
+    Ok(candidate)
+}
+
+// Rank every day by how cleanly file parses as its input, best first.
+fn rank(file: &Path) -> Result<Vec<Candidate>, Box<dyn Error>> {
+    let input = fs::read(file).map_err(|e| format!("{}: {}", file.display(), e))?;
+    let lines = input.split(|b| *b == b'\n').filter(|line| !line.is_empty()).count();
+    let mut candidates = Vec::with_capacity(DAYS.len());
+    for (day_index, solver) in DAYS.iter().enumerate() {
+        if let Some(solver) = solver {
+            candidates.push(try_day(day_index + 1, *solver, &input, lines)?);
+        }
+    }
+    candidates.sort_by(|a, b| b.score().partial_cmp(&a.score()).unwrap().then(a.day.cmp(&b.day)));
+    Ok(candidates)
+}
+
+// Print the days file could be input for, returning whether any parsed cleanly.
+pub fn run(file: &Path, opts: &cli::Cli) -> Result<bool, Box<dyn Error>> {
+    let candidates = rank(file)?;
+    // Some days accept an empty input, but that doesn't make it theirs.
+    let clean: Vec<&Candidate> = candidates.iter().filter(|c| c.lines > 0 && c.bad_lines == 0).collect();
+    match opts.format {
+        Format::Text => {
+            match clean.len() {
+                0 => println!("{}: no day parses cleanly", file.display()),
+                _ => println!("{}: parses as day {}", file.display(),
+                              clean.iter().map(|c| c.day.to_string()).collect::<Vec<_>>().join(", ")),
+            }
+            // The closest misses, in case the file is a damaged input.
+            for candidate in candidates.iter().filter(|c| c.bad_lines > 0 && c.score() > 0.0).take(3) {
+                println!("  Day {:>2}: {:>3.0}% of lines parse, first problem: {}",
+                         candidate.day, candidate.score() * 100.0,
+                         candidate.problem.as_deref().unwrap_or_default());
+            }
+        }
+        Format::Json => for candidate in &candidates {
+            println!("{}", json!({
+                "day": candidate.day,
+                "score": candidate.score(),
+                "bad_lines": candidate.bad_lines,
+                "lines": candidate.lines,
+                "problem": candidate.problem,
+            }));
+        },
+        Format::Csv => {
+            println!("day,score,bad_lines,lines");
+            for candidate in &candidates {
+                println!("{},{},{},{}", candidate.day, candidate.score(), candidate.bad_lines, candidate.lines);
+            }
+        }
+    }
+    Ok(!clean.is_empty())
+}
@@ -0,0 +1,150 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use advent2021::check;
+
+use crate::{cli, web};
+
+// Inputs and answers from src/dXX built into the binary (--features embed-inputs).
+#[cfg(feature = "embed-inputs")]
+mod embedded {
+    include!(concat!(env!("OUT_DIR"), "/embedded.rs"));
+}
+
+#[cfg(feature = "embed-inputs")]
+fn embedded_input(day: u8) -> Option<&'static [u8]> {
+    embedded::INPUTS.iter().find(|(d, _)| *d == day).map(|(_, input)| *input)
+}
+
+#[cfg(not(feature = "embed-inputs"))]
+fn embedded_input(_day: u8) -> Option<&'static [u8]> {
+    None
+}
+
+#[cfg(feature = "embed-inputs")]
+fn embedded_answers(day: u8) -> Option<&'static str> {
+    embedded::ANSWERS.iter().find(|(d, _)| *d == day).map(|(_, answers)| *answers)
+}
+
+#[cfg(not(feature = "embed-inputs"))]
+fn embedded_answers(_day: u8) -> Option<&'static str> {
+    None
+}
+
+// ~/.local/share/advent2021, respecting $XDG_DATA_HOME.
+fn data_dir() -> PathBuf {
+    let data = match env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME").unwrap_or_default()).join(".local").join("share"),
+    };
+    data.join("advent2021")
+}
+
+// Directory holding dXX/input.txt for each day.
+//
+// --input-root or $AOC_INPUT_ROOT if given, else the data dir if it exists, else
+// the src directory this was built from if it still exists, else the data dir
+// (which downloads will create).
+pub fn input_root(opts: &cli::Cli) -> PathBuf {
+    if let Some(root) = &opts.input_root {
+        return root.clone();
+    }
+    let data = data_dir();
+    let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    if !data.exists() && source.exists() {
+        source
+    } else {
+        data
+    }
+}
+
+// Where a day's input comes from.
+enum Input {
+    // -i -
+    Stdin,
+    // Built in with --features embed-inputs.
+    Embedded(&'static [u8]),
+    // ROOT/dXX/input.txt, downloaded when missing.
+    Default(PathBuf),
+    // -i FILE or --input-dir, only downloaded into with --web.
+    Alternate(PathBuf),
+}
+
+// Expand {NN} (zero-padded) and {N} in an --input-pattern.
+fn expand_pattern(pattern: &str, day: u8) -> String {
+    pattern.replace("{NN}", &format!("{:02}", day)).replace("{N}", &day.to_string())
+}
+
+fn default_path(opts: &cli::Cli, day: u8) -> PathBuf {
+    input_root(opts).join(format!("d{:02}", day)).join("input.txt")
+}
+
+fn input_source(opts: &cli::Cli, day: u8) -> Input {
+    match (&opts.input, &opts.input_dir) {
+        (Some(path), _) if path.as_os_str() == cli::STDIN => Input::Stdin,
+        (Some(path), _) => Input::Alternate(path.clone()),
+        (None, Some(dir)) => Input::Alternate(dir.join(expand_pattern(&opts.input_pattern, day))),
+        (None, None) => match embedded_input(day) {
+            // --web and --input-root ask for a file, not what was built in.
+            Some(input) if !opts.web && opts.input_root.is_none() => Input::Embedded(input),
+            _ => Input::Default(default_path(opts, day)),
+        },
+    }
+}
+
+// The file a day's input is read from, for --watch.
+pub fn watch_path(opts: &cli::Cli, day: u8) -> Result<PathBuf, Box<dyn Error>> {
+    match input_source(opts, day) {
+        Input::Stdin => Err("cannot watch stdin".into()),
+        Input::Embedded(_) => Err(format!("day {}'s input is built in; watch a file with -i or --input-root", day).into()),
+        Input::Default(path) | Input::Alternate(path) => Ok(path),
+    }
+}
+
+// Expected answers for a day, if any.
+//
+// These live beside the input: ROOT/dXX/answers.txt for the default input, or
+// foo.answers.txt for an alternate input foo.txt. There are none for stdin.
+pub fn load_answers(opts: &cli::Cli, day: u8) -> Result<check::Answers, Box<dyn Error>> {
+    let path = match input_source(opts, day) {
+        Input::Stdin => return Ok(check::Answers::default()),
+        Input::Embedded(_) => {
+            return Ok(embedded_answers(day).map(check::Answers::from).unwrap_or_default());
+        }
+        Input::Default(path) => path.with_file_name("answers.txt"),
+        Input::Alternate(path) => path.with_extension("answers.txt"),
+    };
+    Ok(check::Answers::load(&path).map_err(|e| format!("{}: {}", path.display(), e))?)
+}
+
+// Read the input for a day, downloading it first if needed.
+//
+// The default input is fetched when it's missing; an alternate input file
+// is only fetched into when --web is given.
+pub fn read_input(opts: &cli::Cli, day: u8) -> Result<Vec<u8>, Box<dyn Error>> {
+    let path = match input_source(opts, day) {
+        Input::Stdin if opts.web => return Err("cannot download input to stdin".into()),
+        Input::Stdin => {
+            let mut input = Vec::new();
+            io::stdin().read_to_end(&mut input).map_err(|e| format!("stdin: {}", e))?;
+            return Ok(input);
+        }
+        Input::Embedded(input) => return Ok(input.to_vec()),
+        Input::Default(path) => {
+            if opts.web || !path.exists() {
+                web::fetch_to(&opts.base_url, &web::session_token()?, day, &path)?;
+            }
+            path
+        }
+        Input::Alternate(path) => {
+            if opts.web {
+                web::fetch_to(&opts.base_url, &web::session_token()?, day, &path)?;
+            }
+            path
+        }
+    };
+    Ok(fs::read(&path).map_err(|e| format!("{}: {}", path.display(), e))?)
+}
@@ -0,0 +1,254 @@
+// Solutions for Advent of Code 2021.
+//
+// solve() is the stable way in for other tools. The rest is public for the
+// advent2021 binary's benefit and may change.
+
+use std::any::Any;
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter};
+use std::io::BufRead;
+use std::time::{Instant, Duration};
+use std::string::ToString;
+
+use advent2021_macros::advent_day;
+
+#[macro_use]
+#[doc(hidden)]
+pub mod util;
+#[macro_use]
+#[doc(hidden)]
+pub mod example;
+#[doc(hidden)]
+pub mod check;
+pub mod ffi;
+pub mod params;
+pub mod parse;
+#[macro_use]
+#[doc(hidden)]
+pub mod trace;
+
+pub struct PartResult {
+    pub answer: String,
+    pub time: Duration,
+}
+
+impl PartResult {
+    pub fn from<F, T>(part: F) -> PartResult
+        where T: ToString, F: FnOnce() -> T
+    {
+        let time = Instant::now();
+        let answer = part().to_string();
+        let time = time.elapsed();
+        PartResult { answer, time }
+    }
+
+    pub fn maybe_from<F, T>(part: F) -> Result<PartResult, Box<dyn StdError>>
+        where T: ToString, F: FnOnce() -> Result<T, Box<dyn StdError>>
+    {
+        let time = Instant::now();
+        let answer = part()?.to_string();
+        let time = time.elapsed();
+        Ok(PartResult { answer, time })
+    }
+}
+
+// Results from running all phases of a day.
+//
+// Parts which weren't selected with --part are None.
+pub struct DayResult {
+    pub parse: Duration,
+    pub part1: Option<PartResult>,
+    pub part2: Option<PartResult>,
+}
+
+// A day's solution, split into phases which the driver times separately.
+//
+// The parsed input is shared between the parts, so neither part should
+// depend on work done by the other.
+pub trait Day {
+    type Input: 'static;
+    fn examples(&self) -> &'static [example::Example] { &[] }
+    fn params(&self) -> &'static [params::Param] { &[] }
+    fn parse(&self, input: &mut dyn BufRead, args: &[params::Arg])
+        -> Result<Self::Input, Box<dyn StdError>>;
+    fn part1(&self, input: &Self::Input, args: &[params::Arg]) -> Result<String, Box<dyn StdError>>;
+    fn part2(&self, input: &Self::Input, args: &[params::Arg]) -> Result<String, Box<dyn StdError>>;
+}
+
+// Object-safe view of a Day, hiding its Input type so days can share a table.
+//
+// Sync so the table can be shared by --jobs worker threads.
+pub trait Solver: Sync {
+    fn examples(&self) -> &'static [example::Example];
+    fn params(&self) -> &'static [params::Param];
+    fn parse(&self, input: &mut dyn BufRead, args: &[params::Arg])
+        -> Result<Box<dyn Any>, Box<dyn StdError>>;
+    fn part1(&self, input: &dyn Any, args: &[params::Arg]) -> Result<String, Box<dyn StdError>>;
+    fn part2(&self, input: &dyn Any, args: &[params::Arg]) -> Result<String, Box<dyn StdError>>;
+
+    // Parse then run both parts, or just part if given, timing each phase.
+    fn solve(&self, input: &mut dyn BufRead, args: &[params::Arg], part: Option<usize>)
+        -> Result<DayResult, Box<dyn StdError>>
+    {
+        let runs_part = |n| part.map_or(true, |part| part == n);
+        let parse = Instant::now();
+        let parsed = self.parse(input, args)?;
+        let parse = parse.elapsed();
+        let part1 = if runs_part(1) {
+            Some(PartResult::maybe_from(|| self.part1(parsed.as_ref(), args))?)
+        } else {
+            None
+        };
+        let part2 = if runs_part(2) {
+            Some(PartResult::maybe_from(|| self.part2(parsed.as_ref(), args))?)
+        } else {
+            None
+        };
+        Ok(DayResult { parse, part1, part2 })
+    }
+}
+
+impl<D: Day + Sync> Solver for D {
+    fn examples(&self) -> &'static [example::Example] { Day::examples(self) }
+
+    fn params(&self) -> &'static [params::Param] { Day::params(self) }
+
+    fn parse(&self, input: &mut dyn BufRead, args: &[params::Arg])
+        -> Result<Box<dyn Any>, Box<dyn StdError>>
+    {
+        Ok(Box::new(Day::parse(self, input, args)?))
+    }
+
+    fn part1(&self, input: &dyn Any, args: &[params::Arg]) -> Result<String, Box<dyn StdError>> {
+        Day::part1(self, input.downcast_ref().expect("input parsed by another day"), args)
+    }
+
+    fn part2(&self, input: &dyn Any, args: &[params::Arg]) -> Result<String, Box<dyn StdError>> {
+        Day::part2(self, input.downcast_ref().expect("input parsed by another day"), args)
+    }
+}
+
+// DAYS[day - 1] solves day, if it's solved yet; see build.rs.
+include!(concat!(env!("OUT_DIR"), "/days.rs"));
+
+// What new-day writes, so the build breaks if the template stops compiling.
+#[cfg(test)]
+mod new_day {
+    include!(concat!(env!("OUT_DIR"), "/new_day.rs"));
+}
+
+// The last day with a solution; earlier days may not have one yet.
+pub const MAX_DAY: usize = DAYS.len();
+
+// The solution for DAYS[day_index], if there is one.
+pub fn solver(day_index: usize) -> Option<&'static dyn Solver> {
+    DAYS.get(day_index).copied().flatten()
+}
+
+
+/// One part's answer from solve().
+#[derive(Debug, Clone, PartialEq)]
+pub struct Answer {
+    pub answer: String,
+    /// Time spent parsing the input.
+    pub parse: Duration,
+    /// Time spent solving the part, after parsing.
+    pub time: Duration,
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.answer)
+    }
+}
+
+/// Why solve() didn't produce an answer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// There's no solution for this day (yet).
+    NoSuchDay(u8),
+    /// Parts are 1 and 2.
+    NoSuchPart(u8),
+    /// A parameter the day doesn't take, or a bad value for one.
+    Param(String),
+    /// The input is malformed; holds every problem found.
+    Parse(Vec<parse::ParseError>),
+    /// The solution failed on this input.
+    Solve(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            Error::NoSuchDay(day) => write!(f, "no solution for day {}", day),
+            Error::NoSuchPart(part) => write!(f, "no part {} (parts are 1 and 2)", part),
+            Error::Param(message) | Error::Solve(message) => write!(f, "{}", message),
+            Error::Parse(errors) => write!(f, "{}", parse::ParseErrors(errors.clone())),
+        }
+    }
+}
+
+impl StdError for Error {}
+
+/// Solve one part of a day's puzzle for input.
+///
+/// params are the day's named parameters as (name, value) pairs, like
+/// `[("steps", "40")]`; any not given take their defaults. See
+/// [params::Param] for what each day takes. Nothing is printed.
+pub fn solve(day: u8, part: u8, input: &str, params: &[(&str, &str)]) -> Result<Answer, Error> {
+    let day_index = usize::from(day).wrapping_sub(1);
+    let solver = solver(day_index).ok_or(Error::NoSuchDay(day))?;
+    if part != 1 && part != 2 {
+        return Err(Error::NoSuchPart(part));
+    }
+    let args: Vec<params::Arg> = params.iter()
+        .map(|(name, value)| params::Arg { name: name.to_string(), value: value.to_string() })
+        .collect();
+    params::check(&args, &[day_index]).map_err(Error::Param)?;
+    let result = solver.solve(&mut input.as_bytes(), &args, Some(usize::from(part)))
+        .map_err(|error| match parse::errors(error) {
+            Ok(mut errors) => {
+                for error in errors.iter_mut() {
+                    error.day = Some(day);
+                }
+                Error::Parse(errors)
+            }
+            Err(error) => Error::Solve(error.to_string()),
+        })?;
+    let answer = if part == 1 { result.part1 } else { result.part2 };
+    let answer = answer.expect("solve runs the part asked for");
+    Ok(Answer { answer: answer.answer, parse: result.parse, time: answer.time })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solve_example() {
+        let input = "199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n";
+        assert_eq!(solve(1, 1, input, &[]).unwrap().answer, "7");
+        assert_eq!(solve(1, 2, input, &[]).unwrap().answer, "5");
+        assert_eq!(solve(6, 1, "3,4,3,1,2\n", &[("days", "18")]).unwrap().answer, "26");
+    }
+
+    #[test]
+    fn new_day_template() {
+        let result = new_day::Day25.solve(&mut "1\n".as_bytes(), &[], None).unwrap();
+        assert_eq!(result.part1.unwrap().answer, "unimplemented");
+        assert_eq!(result.part2.unwrap().answer, "unimplemented");
+    }
+
+    #[test]
+    fn solve_errors() {
+        assert_eq!(solve(25, 1, "", &[]), Err(Error::NoSuchDay(25)));
+        assert_eq!(solve(0, 1, "", &[]), Err(Error::NoSuchDay(0)));
+        assert_eq!(solve(1, 3, "", &[]), Err(Error::NoSuchPart(3)));
+        assert!(matches!(solve(1, 1, "1\n", &[("days", "1")]), Err(Error::Param(_))));
+        assert!(matches!(solve(6, 1, "3,4\n", &[("days", "x")]), Err(Error::Param(_))));
+        match solve(1, 1, "1\nx\n", &[]) {
+            Err(Error::Parse(errors)) => assert_eq!((errors[0].day, errors[0].line), (Some(1), 2)),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+}
@@ -1,132 +1,318 @@
+use std::collections::HashMap;
 use std::error::Error;
-use std::io::BufRead;
 use std::time::{Instant, Duration};
-use std::string::ToString;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
 
+use advent2021::{check, example, params, parse, trace, util, Solver};
+
+mod baseline;
+mod bench;
+mod cache;
 mod cli;
-mod util;
-mod d01;
-mod d02;
-mod d03;
-mod d04;
-mod d05;
-mod d06;
-mod d07;
-mod d08;
-mod d09;
-mod d10;
-mod d11;
-mod d12;
-mod d13;
-mod d14;
-//mod d15;
-//mod d16;
-//mod d17;
-//mod d18;
-//mod d19;
-//mod d20;
-//mod d21;
-//mod d22;
-//mod d23;
-//mod d24;
-//mod d25;
+mod history;
+mod identify;
+mod input;
+mod report;
+mod scaffold;
+mod serve;
+mod watch;
+mod web;
 
-pub struct PartResult {
-    answer: String,
-    time: Duration,
+// Run a day, filling in its record.
+fn solve_day(opts: &cli::Cli, day: &dyn Solver, record: &mut report::Record)
+    -> Result<(), Box<dyn Error>>
+{
+    let (input, opts, answers) = match &opts.example {
+        Some(name) => {
+            let example = example::find(day, name.as_deref())?;
+            record.example = Some(example.name.to_string());
+            let answers = check::Answers::from(&example.answers_text());
+            // The example's own args come first, so any the user gave win.
+            let mut opts = opts.clone();
+            opts.args = example.args().into_iter().chain(opts.args).collect();
+            (example.input.as_bytes().to_vec(), opts, answers)
+        }
+        None => {
+            let input_clock = Instant::now();
+            let input = input::read_input(opts, record.day as u8)?;
+            record.input = input_clock.elapsed();
+            record.input_hash = Some(history::hash(&input));
+            let answers = if opts.check {
+                input::load_answers(opts, record.day as u8)?
+            } else {
+                check::Answers::default()
+            };
+            (input, opts.clone(), answers)
+        }
+    };
+    if opts.validate {
+        let parse_clock = Instant::now();
+        let parsed = day.parse(&mut input.as_slice(), &opts.args);
+        record.parse = parse_clock.elapsed();
+        record.problems = Some(match parsed {
+            Ok(_) => Vec::new(),
+            Err(error) => parse::errors(error)?,
+        });
+        return Ok(());
+    }
+    let answers = if opts.check { Some(&answers) } else { None };
+    match opts.bench {
+        Some(runs) => {
+            let (result, bench) = bench::run(day, &input, &opts, runs)?;
+            record.set_result(result, answers);
+            record.set_bench(bench);
+        }
+        None if (opts.cache || opts.no_cache) && record.example.is_none() => {
+            let (result, cached) = cache::solve(day, record.day, &input, &opts)?;
+            record.set_result(result, answers);
+            for (part, cached) in record.parts.iter_mut().zip(cached) {
+                if let Some(part) = part {
+                    part.cached = cached;
+                }
+            }
+        }
+        None => record.set_result(day.solve(&mut input.as_slice(), &opts.args, opts.part)?, answers),
+    }
+    Ok(())
 }
 
-impl PartResult {
-    pub fn new() -> PartResult {
-        PartResult { answer: "unimplemented".into(), time: Duration::new(0, 0) }
+// Run a day and print its results.
+fn day_record(opts: &cli::Cli, day_index: usize) -> report::Record {
+    let mut record = report::Record::new(day_index + 1);
+    let day = match advent2021::solver(day_index) {
+        Some(day) => day,
+        None => {
+            record.error = Some(report::UNIMPLEMENTED.into());
+            return record;
+        }
+    };
+    if let Err(mut error) = trace::with_level(trace::level(&opts.verbose, record.day as u8),
+                                              || solve_day(opts, day, &mut record)) {
+        parse::set_day(&mut error, record.day as u8);
+        record.error = Some(error.to_string());
     }
+    record
+}
 
-    pub fn from<F, T>(part: F) -> PartResult
-        where T: ToString, F: FnOnce() -> T
-    {
-        let time = Instant::now();
-        let answer = part().to_string();
-        let time = time.elapsed();
-        PartResult { answer, time }
+// Compare a finished day against the baseline, if any, and print it.
+fn print_day(opts: &cli::Cli, baseline: Option<&baseline::Baseline>, record: &mut report::Record) {
+    if record.error.is_none() {
+        record.compare = baseline.and_then(|baseline| baseline.compare(record, opts.threshold));
     }
+    report::print_record(opts.format, opts.time, record);
+}
 
-    pub fn maybe_from<F, T>(part: F) -> Result<PartResult, Box<dyn Error>>
-        where T: ToString, F: FnOnce() -> Result<T, Box<dyn Error>>
-    {
-        let time = Instant::now();
-        let answer = part()?.to_string();
-        let time = time.elapsed();
-        Ok(PartResult { answer, time })
+fn run_day(opts: &cli::Cli, baseline: Option<&baseline::Baseline>, day_index: usize)
+    -> report::Record
+{
+    report::print_start(opts.format, day_index + 1);
+    let mut record = day_record(opts, day_index);
+    print_day(opts, baseline, &mut record);
+    record
+}
+
+// Run days on a pool of worker threads, printing each in day order once it
+// and every day before it are done.
+//
+// Verbose output is captured per day and printed along with the day's result.
+fn run_days_parallel(opts: &cli::Cli, baseline: Option<&baseline::Baseline>,
+                     day_indices: Vec<usize>, jobs: usize)
+    -> Vec<report::Record>
+{
+    let opts = Arc::new(opts.clone());
+    let queue = Arc::new(Mutex::new(day_indices.clone().into_iter()));
+    let (sender, receiver) = mpsc::channel();
+    let workers: Vec<_> = (0..jobs.min(day_indices.len())).map(|_| {
+        let (opts, queue, sender) = (opts.clone(), queue.clone(), sender.clone());
+        thread::spawn(move || loop {
+            let next = queue.lock().unwrap().next();
+            let day_index = match next {
+                Some(day_index) => day_index,
+                None => break,
+            };
+            let (record, output) = util::capture_verbose(|| day_record(&opts, day_index));
+            if sender.send((day_index, record, output)).is_err() {
+                break;
+            }
+        })
+    }).collect();
+    drop(sender);
+
+    let mut done = HashMap::new();
+    let mut records = Vec::new();
+    for day_index in day_indices {
+        while !done.contains_key(&day_index) {
+            match receiver.recv() {
+                Ok((index, record, output)) => { done.insert(index, (record, output)); }
+                // A worker panicked; report the day rather than hang.
+                Err(_) => {
+                    let mut record = report::Record::new(day_index + 1);
+                    record.error = Some("worker thread panicked".into());
+                    done.insert(day_index, (record, String::new()));
+                }
+            }
+        }
+        let (mut record, output) = done.remove(&day_index).unwrap();
+        report::print_start(opts.format, record.day);
+        util::print_verbose(&output);
+        print_day(&opts, baseline, &mut record);
+        records.push(record);
+    }
+    for worker in workers {
+        let _ = worker.join();
     }
+    records
 }
 
-pub trait Day {
-    fn mod_path(&self) -> &str;
-    fn run(&self, input: &mut dyn BufRead, cli: &cli::Cli) -> Result<(PartResult, PartResult), Box<dyn Error>>;
+// Number of failed checks, phases slower than the baseline, inputs which
+// failed --validate, and days which couldn't be run.
+#[derive(Default)]
+struct Outcome {
+    failures: usize,
+    regressions: usize,
+    invalid: usize,
+    errors: usize,
 }
 
-const DAYS: [&dyn Day; 14] = [
-    &d01::Day1{},
-    &d02::Day2{},
-    &d03::Day3{},
-    &d04::Day4{},
-    &d05::Day5{},
-    &d06::Day6{},
-    &d07::Day7{},
-    &d08::Day8{},
-    &d09::Day9{},
-    &d10::Day10{},
-    &d11::Day11{},
-    &d12::Day12{},
-    &d13::Day13{},
-    &d14::Day14{},
-];
+impl Outcome {
+    fn add(&mut self, record: &report::Record) {
+        self.failures += record.failures();
+        self.regressions += record.regressions();
+        if record.problems.as_ref().map_or(false, |problems| !problems.is_empty()) {
+            self.invalid += 1;
+        }
+        if record.error.as_deref().map_or(false, |error| error != report::UNIMPLEMENTED) {
+            self.errors += 1;
+        }
+    }
+}
 
-const MAX_DAY: usize = DAYS.len();
+// Run the selected days.
+fn run(opts: &cli::Cli) -> Result<(Outcome, Vec<report::Record>), Box<dyn Error>> {
+    let mut outcome = Outcome::default();
+    if let Some(name) = &opts.save_baseline {
+        // Before running anything, rather than failing to save at the end.
+        baseline::check_name(name)?;
+    }
+    let baseline = match &opts.compare {
+        Some(name) => Some(baseline::Baseline::load(name)?),
+        None => None,
+    };
+    let single = opts.day.len() == 1;
+    if !single && opts.input.is_some() {
+        return Err("cannot specify -i with multiple days (see --input-dir)".into());
+    }
+    let day_indices: Vec<usize> = opts.day.iter().map(|day| usize::from(day) - 1).collect();
+    params::check(&opts.args, &day_indices).map_err(|e| format!("{} (see --help)", e))?;
+    report::print_header(opts.format, opts.time);
+    let clock = Instant::now();
+    let records: Vec<report::Record> = match opts.jobs() {
+        1 => day_indices.into_iter()
+            .map(|day_index| run_day(opts, baseline.as_ref(), day_index))
+            .collect(),
+        jobs => run_days_parallel(opts, baseline.as_ref(), day_indices, jobs),
+    };
+    let mut cpu_time = Duration::new(0, 0);
+    for record in &records {
+        outcome.add(record);
+        cpu_time += record.total();
+    }
+    if !single && opts.time && opts.format == report::Format::Text {
+        println!("Total runtime: {:?} wall clock, {:?} summed over days", clock.elapsed(), cpu_time);
+    }
+    if let Some(name) = &opts.save_baseline {
+        let path = baseline::Baseline::save(name, &records)?;
+        eprintln!("Saved baseline '{}' to {}", name, path.display());
+    }
+    if !opts.no_history {
+        // Losing a history entry isn't worth failing the run over.
+        if let Err(error) = history::append(opts, &records) {
+            eprintln!("WARNING: not recorded in history: {}", error);
+        }
+    }
+    Ok((outcome, records))
+}
 
-fn run_day(opts: &cli::Cli, day_index: usize) -> Result<(), Box<dyn Error>> {
-    println!("Day {}:", day_index + 1);
-    if day_index >= MAX_DAY {
-        println!("unimplemented");
-    }
-    else {
-        let day = &DAYS[day_index];
-        let input_clock = Instant::now();
-        let mut input = util::read_input(opts, day.mod_path())?;
-        let input_clock = input_clock.elapsed();
-        let (part1, part2) = day.run(input.as_mut(), &opts)?;
-        println!("  Part 1: {}\n  Part 2: {}", part1.answer, part2.answer);
-        if opts.time {
-            println!("  Time {:<16?} {:<12?} {:<12?} {:<12?}",
-                input_clock, part1.time, part2.time,
-                input_clock + part1.time + part2.time);
-        }
-        println!();
+// Say what went wrong in a run, if anything, returning whether anything did.
+fn print_outcome(outcome: &Outcome) -> bool {
+    if outcome.errors > 0 {
+        eprintln!("{} day(s) failed to run", outcome.errors);
     }
-    Ok(())
+    if outcome.failures > 0 {
+        eprintln!("{} part(s) failed check", outcome.failures);
+    }
+    if outcome.invalid > 0 {
+        eprintln!("{} input(s) failed validation", outcome.invalid);
+    }
+    if outcome.regressions > 0 {
+        eprintln!("{} phase(s) slower than baseline", outcome.regressions);
+    }
+    outcome.errors + outcome.failures + outcome.invalid + outcome.regressions > 0
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
+fn main() {
     use structopt::StructOpt;
-    let opts = cli::Cli::from_args();
-    if opts.time {
-        println!(" Times: {:<16} {:<12} {:<12} {:<12}", "input", "part 1", "part 2", "total");
+    // Day parameters are listed at the end of --help.
+    let day_params = params::help();
+    let opts = cli::Cli::from_clap(&cli::Cli::clap().after_help(day_params.as_str()).get_matches());
+    // Keep stdout parseable for machine-readable formats.
+    util::set_verbose_to_stderr(opts.format != report::Format::Text);
+    if let Some(path) = &opts.trace_file {
+        if let Err(error) = util::set_trace_file(path) {
+            eprintln!("ERROR: {}: {}", path.display(), error);
+            std::process::exit(1);
+        }
     }
-    if opts.day.0 == opts.day.1 {
-        return run_day(&opts, (opts.day.0 - 1).into());
+    if let Some(cli::Command::Identify { file }) = &opts.command {
+        match identify::run(file, &opts) {
+            Ok(true) => (),
+            Ok(false) => std::process::exit(1),
+            Err(error) => {
+                eprintln!("ERROR: {}", error);
+                std::process::exit(1);
+            }
+        }
+        return;
     }
-    else if opts.input.is_some() {
-        println!("ERROR: cannot specify -i with multiple days");
-        return Ok(());
+    if let Some(cli::Command::History { day }) = &opts.command {
+        if let Err(error) = history::run(*day, &opts) {
+            eprintln!("ERROR: {}", error);
+            std::process::exit(1);
+        }
+        return;
     }
-    let day_start: usize = (opts.day.0 - 1).into();
-    let day_end: usize = MAX_DAY.min((opts.day.1 + 1).into());
-    let clock = Instant::now();
-    for day_index in day_start..day_end {
-        run_day(&opts, day_index)?;
+    if let Some(cli::Command::NewDay { day, src }) = &opts.command {
+        if let Err(error) = scaffold::run(*day, src, &opts) {
+            eprintln!("ERROR: {}", error);
+            std::process::exit(1);
+        }
+        return;
     }
-    if opts.time {
-        println!("Total runtime: {:?}", clock.elapsed());
+    if let Some(cli::Command::Serve { port, address, workers, max_body }) = &opts.command {
+        if let Err(error) = serve::run(address, *port, *workers, *max_body) {
+            eprintln!("ERROR: {}", error);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if opts.watch {
+        // Only returns if there's nothing to watch.
+        if let Err(error) = watch::run(&opts) {
+            eprintln!("ERROR: {}", error);
+            std::process::exit(1);
+        }
+        return;
+    }
+    let outcome = run(&opts);
+    util::flush_trace_file();
+    match outcome {
+        Ok((outcome, _)) => if print_outcome(&outcome) {
+            std::process::exit(1);
+        },
+        Err(error) => {
+            eprintln!("ERROR: {}", error);
+            std::process::exit(1);
+        }
     }
-    Ok(())
 }
@@ -0,0 +1,114 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::{solver, DAYS};
+
+// A named parameter a day takes from --args NAME=VALUE, like the number of steps to simulate.
+pub struct Param {
+    pub name: &'static str,
+    pub kind: Kind,
+    pub default: &'static str,
+    pub help: &'static str,
+}
+
+// The values a parameter accepts.
+pub enum Kind {
+    // A whole number from min to max, inclusive.
+    Count { min: usize, max: usize },
+}
+
+impl Kind {
+    fn check(&self, value: &str) -> Result<(), String> {
+        match *self {
+            Kind::Count { min, max } => match value.parse::<usize>() {
+                Ok(count) if (min..=max).contains(&count) => Ok(()),
+                _ => Err(format!("expected a whole number from {} to {}", min, max)),
+            },
+        }
+    }
+}
+
+impl Display for Kind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            Kind::Count { min, max } => write!(f, "{}-{}", min, max),
+        }
+    }
+}
+
+// One NAME=VALUE from --args.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arg {
+    pub name: String,
+    pub value: String,
+}
+
+impl FromStr for Arg {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Arg, String> {
+        match s.split_once('=') {
+            Some((name, value)) => Ok(Arg { name: name.to_string(), value: value.to_string() }),
+            None => Err(format!("expected NAME=VALUE, found '{}'", s)),
+        }
+    }
+}
+
+impl Display for Arg {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}={}", self.name, self.value)
+    }
+}
+
+fn find<'a>(params: &'a [Param], name: &str) -> Option<&'a Param> {
+    params.iter().find(|param| param.name == name)
+}
+
+// Check --args against the parameters of the selected days.
+//
+// Each argument must name a parameter of at least one selected day, and its value must suit
+// every day that takes it. Days ignore parameters they don't take, so one --args can cover
+// several days.
+pub fn check(args: &[Arg], day_indices: &[usize]) -> Result<(), String> {
+    for arg in args {
+        let mut known = false;
+        for &day_index in day_indices {
+            let params = solver(day_index).map_or(&[][..], |day| day.params());
+            if let Some(param) = find(params, &arg.name) {
+                known = true;
+                param.kind.check(&arg.value)
+                    .map_err(|e| format!("day {}: parameter {}: {}", day_index + 1, arg, e))?;
+            }
+        }
+        if !known {
+            return Err(format!("no selected day takes parameter '{}'", arg.name));
+        }
+    }
+    Ok(())
+}
+
+// The value of parameter name from --args, or its default. The last --args for it wins.
+pub fn get<T>(params: &[Param], args: &[Arg], name: &str) -> Result<T, Box<dyn Error>>
+    where T: FromStr, T::Err: Error + 'static
+{
+    let param = find(params, name).ok_or_else(|| format!("no parameter '{}'", name))?;
+    let mut value = param.default;
+    for arg in args.iter().filter(|arg| arg.name == name) {
+        param.kind.check(&arg.value).map_err(|e| format!("parameter {}: {}", arg, e))?;
+        value = &arg.value;
+    }
+    Ok(value.parse()?)
+}
+
+// Help listing every day's parameters, for the end of --help.
+pub fn help() -> String {
+    let mut help = String::from("DAY PARAMETERS (--args NAME=VALUE):\n");
+    for (day_index, day) in DAYS.iter().enumerate() {
+        for param in day.map_or(&[][..], |day| day.params()) {
+            let usage = format!("{}={}", param.name, param.kind);
+            help += &format!("    Day {:>2}: {:<16} {} [default: {}]\n",
+                             day_index + 1, usage, param.help, param.default);
+        }
+    }
+    help
+}
@@ -0,0 +1,220 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::{BufRead, Lines as IoLines};
+use std::str::FromStr;
+
+// A malformed input, pointing at where in the input it went wrong.
+//
+// Displays as one line, like:
+//
+//   day 2, line 3, column 9: invalid number 'x' (invalid digit found in string)
+//
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    // Filled in by the driver, which knows which day was parsing.
+    pub day: Option<u8>,
+    // 1-based; 0 when the problem isn't on any one line, like missing input.
+    pub line: usize,
+    // 1-based, in characters.
+    pub column: usize,
+    // The offending text, if any.
+    pub text: String,
+    pub message: String,
+}
+
+impl ParseError {
+    // A problem with the input as a whole rather than one line.
+    pub fn input(message: impl Into<String>) -> ParseError {
+        ParseError { day: None, line: 0, column: 0, text: String::new(), message: message.into() }
+    }
+
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        if let Some(day) = self.day {
+            write!(f, "day {}, ", day)?;
+        }
+        match (self.line, self.column) {
+            (0, _) => write!(f, "input: ")?,
+            (line, 0) => write!(f, "line {}: ", line)?,
+            (line, column) => write!(f, "line {}, column {}: ", line, column)?,
+        }
+        write!(f, "{}", self.message)?;
+        if !self.text.is_empty() {
+            write!(f, ": '{}'", self.text)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ParseError {}
+
+// Every problem found parsing an input.
+//
+// Parsers carry on past a bad line where they can, so that --validate can
+// report everything wrong with an input at once. Displays as the first error.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParseErrors(pub Vec<ParseError>);
+
+impl ParseErrors {
+    pub fn push(&mut self, error: ParseError) {
+        self.0.push(error);
+    }
+
+    // Keep the error from result, if any.
+    pub fn check<T>(&mut self, result: Result<T, ParseError>) -> Option<T> {
+        result.map_err(|error| self.push(error)).ok()
+    }
+
+    pub fn append(&mut self, mut errors: ParseErrors) {
+        self.0.append(&mut errors.0);
+    }
+
+    // Keep the errors from result, if any.
+    pub fn check_all<T>(&mut self, result: Result<T, ParseErrors>) -> Option<T> {
+        result.map_err(|errors| self.append(errors)).ok()
+    }
+
+    // value, unless there were any errors.
+    pub fn or<T>(self, value: T) -> Result<T, ParseErrors> {
+        if self.0.is_empty() { Ok(value) } else { Err(self) }
+    }
+}
+
+impl From<ParseError> for ParseErrors {
+    fn from(error: ParseError) -> ParseErrors {
+        ParseErrors(vec![error])
+    }
+}
+
+impl Display for ParseErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self.0.as_slice() {
+            [] => write!(f, "parse failed"),
+            [error] => write!(f, "{}", error),
+            [error, rest @ ..] => write!(f, "{} (and {} more; see --validate)", error, rest.len()),
+        }
+    }
+}
+
+impl Error for ParseErrors {}
+
+// Collect every item, or every error if there were any.
+pub fn collect<T>(items: impl Iterator<Item = Result<T, ParseError>>) -> Result<Vec<T>, ParseErrors> {
+    let mut errors = ParseErrors::default();
+    let values = items.filter_map(|item| errors.check(item)).collect();
+    errors.or(values)
+}
+
+// The parse errors in error, or error itself if it isn't from parsing.
+pub fn errors(error: Box<dyn Error>) -> Result<Vec<ParseError>, Box<dyn Error>> {
+    let error = match error.downcast::<ParseErrors>() {
+        Ok(errors) => return Ok(errors.0),
+        Err(error) => error,
+    };
+    match error.downcast::<ParseError>() {
+        Ok(error) => Ok(vec![*error]),
+        Err(error) => Err(error),
+    }
+}
+
+// Attach the day to any parse errors in error.
+pub fn set_day(error: &mut Box<dyn Error>, day: u8) {
+    if let Some(error) = error.downcast_mut::<ParseError>() {
+        error.day = Some(day);
+    } else if let Some(errors) = error.downcast_mut::<ParseErrors>() {
+        for error in errors.0.iter_mut() {
+            error.day = Some(day);
+        }
+    }
+}
+
+// One line of input, remembering where it came from.
+#[derive(Debug, Clone)]
+pub struct Line {
+    // 1-based.
+    pub number: usize,
+    pub text: String,
+}
+
+impl Line {
+    pub fn new(number: usize, text: String) -> Line {
+        Line { number, text }
+    }
+
+    // Column of at, which should be a slice of this line's text; 0 if it isn't.
+    fn column(&self, at: &str) -> usize {
+        let start = self.text.as_ptr() as usize;
+        let offset = (at.as_ptr() as usize).wrapping_sub(start);
+        if offset <= self.text.len() && self.text.is_char_boundary(offset) {
+            self.text[..offset].chars().count() + 1
+        } else {
+            0
+        }
+    }
+
+    // An error at at, a slice of this line's text.
+    pub fn error(&self, at: &str, message: impl Into<String>) -> ParseError {
+        ParseError {
+            day: None,
+            line: self.number,
+            column: self.column(at),
+            text: at.to_string(),
+            message: message.into(),
+        }
+    }
+
+    // An error about the whole line.
+    pub fn error_line(&self, message: impl Into<String>) -> ParseError {
+        self.error(&self.text, message)
+    }
+
+    // The first character (as a slice of the text) which isn't valid, if any.
+    pub fn invalid_char(&self, valid: impl Fn(char) -> bool) -> Option<&str> {
+        self.text.char_indices()
+            .find(|(_, c)| !valid(*c))
+            .map(|(index, c)| &self.text[index..index + c.len_utf8()])
+    }
+
+    // Parse field, a slice of this line's text, as a what.
+    pub fn parse<T>(&self, field: &str, what: &str) -> Result<T, ParseError>
+        where T: FromStr, T::Err: Display
+    {
+        field.parse().map_err(|e| self.error(field, format!("invalid {} ({})", what, e)))
+    }
+
+    // Split part, a slice of this line's text, around the first sep.
+    pub fn split_once<'a>(&self, part: &'a str, sep: &str) -> Result<(&'a str, &'a str), ParseError> {
+        part.split_once(sep).ok_or_else(|| self.error(part, format!("expected '{}'", sep)))
+    }
+}
+
+// Iterator over numbered lines of input.
+pub struct Lines<'a> {
+    lines: IoLines<&'a mut dyn BufRead>,
+    number: usize,
+}
+
+impl Iterator for Lines<'_> {
+    type Item = Result<Line, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = self.lines.next()?;
+        self.number += 1;
+        Some(match line {
+            Ok(text) => Ok(Line::new(self.number, text)),
+            Err(error) => Err(ParseError {
+                day: None,
+                line: self.number,
+                column: 0,
+                text: String::new(),
+                message: error.to_string(),
+            }),
+        })
+    }
+}
+
+pub fn lines(input: &mut dyn BufRead) -> Lines<'_> {
+    Lines { lines: input.lines(), number: 0 }
+}
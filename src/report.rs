@@ -0,0 +1,366 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde_json::json;
+
+use advent2021::check::{self, Status};
+use advent2021::parse::ParseError;
+use advent2021::{DayResult, PartResult};
+
+use crate::baseline::Comparison;
+use crate::bench::{Bench, Stats};
+
+pub const UNIMPLEMENTED: &str = "unimplemented";
+
+// Output format for results.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s.trim().to_lowercase().as_str() {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("unknown format '{}' (expected text, json, or csv)", s)),
+        }
+    }
+}
+
+// One part's answer and how it checked out.
+pub struct PartRecord {
+    pub answer: String,
+    pub time: Duration,
+    // None unless --check was given.
+    pub check: Option<Status>,
+    pub expected: Option<String>,
+    // The answer came from --cache rather than being solved.
+    pub cached: bool,
+}
+
+impl PartRecord {
+    fn from(part: usize, result: PartResult, answers: Option<&check::Answers>) -> PartRecord {
+        let check = answers.map(|answers| answers.check(part, &result.answer));
+        let expected = answers.and_then(|answers| answers.expected(part)).map(String::from);
+        PartRecord { answer: result.answer, time: result.time, check, expected, cached: false }
+    }
+}
+
+// Everything reported about one day.
+pub struct Record {
+    pub day: usize,
+    pub example: Option<String>,
+    pub input: Duration,
+    pub parse: Duration,
+    // None for a skipped part.
+    pub parts: [Option<PartRecord>; 2],
+    pub error: Option<String>,
+    // Only with --bench.
+    pub bench: Option<Bench>,
+    // Only with --compare, for days in the baseline.
+    pub compare: Option<Comparison>,
+    // Only with --validate: everything wrong with the input.
+    pub problems: Option<Vec<ParseError>>,
+    // Hash of the input, for the run history.
+    pub input_hash: Option<u64>,
+}
+
+impl Record {
+    pub fn new(day: usize) -> Record {
+        Record {
+            day,
+            example: None,
+            input: Duration::new(0, 0),
+            parse: Duration::new(0, 0),
+            parts: [None, None],
+            error: None,
+            bench: None,
+            compare: None,
+            problems: None,
+            input_hash: None,
+        }
+    }
+
+    // Fill in results, checking them against answers if given.
+    pub fn set_result(&mut self, result: DayResult, answers: Option<&check::Answers>) {
+        self.parse = result.parse;
+        self.parts = [
+            result.part1.map(|part| PartRecord::from(1, part, answers)),
+            result.part2.map(|part| PartRecord::from(2, part, answers)),
+        ];
+    }
+
+    // Report benchmark timings, using the median as each phase's time.
+    pub fn set_bench(&mut self, bench: Bench) {
+        self.parse = bench.parse.median;
+        for (part, stats) in self.parts.iter_mut().zip(&bench.parts) {
+            if let (Some(part), Some(stats)) = (part, stats) {
+                part.time = stats.median;
+            }
+        }
+        self.bench = Some(bench);
+    }
+
+    pub fn total(&self) -> Duration {
+        self.input + self.parse + self.parts.iter().flatten().map(|part| part.time).sum::<Duration>()
+    }
+
+    // Number of phases slower than the --compare baseline allows.
+    pub fn regressions(&self) -> usize {
+        self.compare.as_ref().map_or(0, |compare| compare.regressions())
+    }
+
+    // Number of parts which failed --check.
+    pub fn failures(&self) -> usize {
+        self.parts.iter().flatten().filter(|part| part.check == Some(Status::Fail)).count()
+    }
+}
+
+fn nanos(time: Duration) -> u64 {
+    time.as_nanos() as u64
+}
+
+// Print the header, if any, before the first record.
+pub fn print_header(format: Format, time: bool) {
+    match format {
+        Format::Text => if time {
+            println!(" Times: {:<16} {:<12} {:<12} {:<12} {:<12}",
+                     "input", "parse", "part 1", "part 2", "total");
+        },
+        Format::Json => (),
+        Format::Csv => println!("{}", CSV_COLUMNS.join(",")),
+    }
+}
+
+// Print what's known about a day before running it.
+//
+// For text output this comes first so verbose output appears under it.
+pub fn print_start(format: Format, day: usize) {
+    if format == Format::Text {
+        println!("Day {}:", day);
+    }
+}
+
+pub fn print_record(format: Format, time: bool, record: &Record) {
+    match format {
+        Format::Text => print_text(time, record),
+        Format::Json => println!("{}", to_json(record)),
+        Format::Csv => println!("{}", to_csv(record)),
+    }
+}
+
+fn print_text(time: bool, record: &Record) {
+    if let Some(example) = &record.example {
+        println!("  Example: {}", example);
+    }
+    match record.error.as_deref() {
+        Some(UNIMPLEMENTED) => {
+            println!("{}", UNIMPLEMENTED);
+            return;
+        }
+        Some(error) => {
+            println!("  ERROR: {}\n", error);
+            return;
+        }
+        None => (),
+    }
+    if let Some(problems) = &record.problems {
+        print_problems(time, record, problems);
+        return;
+    }
+    for (index, part) in record.parts.iter().enumerate() {
+        let part_num = index + 1;
+        match part {
+            None => println!("  Part {}: skipped", part_num),
+            Some(PartRecord { answer, check: None, cached, .. }) =>
+                println!("  Part {}: {}{}", part_num, answer, if *cached { " (cached)" } else { "" }),
+            Some(PartRecord { answer, check: Some(status), expected, cached, .. }) => {
+                println!("  Part {}: [{}] {}{}", part_num, status, answer, if *cached { " (cached)" } else { "" });
+                if *status == Status::Fail {
+                    println!("    expected: {}", expected.as_deref().unwrap_or_default());
+                }
+            }
+        }
+    }
+    if let Some(bench) = &record.bench {
+        print_bench(bench);
+    }
+    if time {
+        let [part1, part2] = [&record.parts[0], &record.parts[1]].map(|part| {
+            part.as_ref().map_or("skipped".to_string(), |part| format!("{:?}", part.time))
+        });
+        println!("  Time {:<16?} {:<12?} {:<12} {:<12} {:<12?}",
+            record.input, record.parse, part1, part2, record.total());
+    }
+    if let Some(compare) = &record.compare {
+        print_compare(compare);
+    }
+    println!();
+}
+
+fn print_problems(time: bool, record: &Record, problems: &[ParseError]) {
+    match problems.len() {
+        0 => println!("  Input OK"),
+        count => println!("  {} problem(s):", count),
+    }
+    for problem in problems {
+        println!("    {}", problem);
+    }
+    if time {
+        println!("  Time {:<16?} {:<12?}", record.input, record.parse);
+    }
+    println!();
+}
+
+fn print_bench(bench: &Bench) {
+    println!("  Bench ({} runs): {:<12} {:<12} {:<12} {:<12}",
+             bench.runs, "min", "median", "mean", "stddev");
+    let phases = [("parse", Some(&bench.parse)), ("part 1", bench.parts[0].as_ref()),
+                  ("part 2", bench.parts[1].as_ref())];
+    for (name, stats) in phases.iter() {
+        if let Some(stats) = stats {
+            println!("    {:<14} {:<12?} {:<12?} {:<12?} {:<12?}",
+                     name, stats.min, stats.median, stats.mean, stats.stddev);
+        }
+    }
+}
+
+fn phase_name(phase: &str) -> &str {
+    match phase {
+        "part1" => "part 1",
+        "part2" => "part 2",
+        _ => phase,
+    }
+}
+
+fn print_compare(compare: &Comparison) {
+    let changes: Vec<String> = compare.changes.iter().map(|change| {
+        format!("{} {:+.1}%{}", phase_name(change.phase), change.percent(),
+                if change.slower { " SLOWER" } else { "" })
+    }).collect();
+    println!("  vs {}: {}", compare.baseline, changes.join(", "));
+    for change in compare.changes.iter().filter(|change| change.slower) {
+        println!("    {}: {:?} -> {:?} (threshold {}%)",
+                 phase_name(change.phase), change.baseline, change.time, compare.threshold);
+    }
+}
+
+fn compare_json(compare: &Comparison) -> serde_json::Value {
+    let mut phases = serde_json::Map::new();
+    for change in &compare.changes {
+        phases.insert(change.phase.to_string(), json!({
+            "baseline_ns": nanos(change.baseline),
+            "time_ns": nanos(change.time),
+            "change_pct": change.percent(),
+            "slower": change.slower,
+        }));
+    }
+    json!({
+        "baseline": compare.baseline,
+        "threshold_pct": compare.threshold,
+        "phases": phases,
+    })
+}
+
+fn stats_json(stats: &Stats) -> serde_json::Value {
+    json!({
+        "min_ns": nanos(stats.min),
+        "median_ns": nanos(stats.median),
+        "mean_ns": nanos(stats.mean),
+        "stddev_ns": nanos(stats.stddev),
+    })
+}
+
+pub fn to_json(record: &Record) -> serde_json::Value {
+    let part = |index: usize| record.parts[index].as_ref().map(|part| json!({
+        "answer": part.answer,
+        "time_ns": nanos(part.time),
+        "check": part.check.map(|status| status.to_string()),
+        "cached": part.cached,
+    }));
+    json!({
+        "day": record.day,
+        "example": record.example,
+        "input_ns": nanos(record.input),
+        "parse_ns": nanos(record.parse),
+        "part1": part(0),
+        "part2": part(1),
+        "total_ns": nanos(record.total()),
+        "error": record.error,
+        "bench": record.bench.as_ref().map(|bench| json!({
+            "runs": bench.runs,
+            "parse": stats_json(&bench.parse),
+            "part1": bench.parts[0].as_ref().map(stats_json),
+            "part2": bench.parts[1].as_ref().map(stats_json),
+        })),
+        "compare": record.compare.as_ref().map(compare_json),
+        "problems": record.problems.as_ref().map(|problems| problems.iter().map(|problem| json!({
+            "line": problem.line,
+            "column": problem.column,
+            "text": problem.text,
+            "message": problem.message,
+        })).collect::<Vec<_>>()),
+    })
+}
+
+// With --bench, the times are medians over bench_runs runs.
+// slower lists the phases slower than the --compare baseline, and problems
+// counts what --validate found wrong with the input. cached lists the parts
+// answered from --cache.
+const CSV_COLUMNS: [&str; 17] = [
+    "day", "example", "part1", "part2", "input_ns", "parse_ns", "part1_ns", "part2_ns",
+    "total_ns", "part1_check", "part2_check", "error", "skipped", "bench_runs", "slower",
+    "problems", "cached",
+];
+
+// Quote a CSV field if it has anything special in it.
+pub fn csv_field(field: &str) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn to_csv(record: &Record) -> String {
+    let part = |index: usize| record.parts[index].as_ref();
+    let skipped: Vec<String> = (0..2)
+        .filter(|index| part(*index).is_none() && record.error.is_none() && record.problems.is_none())
+        .map(|index| (index + 1).to_string())
+        .collect();
+    let cached: Vec<String> = (0..2)
+        .filter(|index| part(*index).map_or(false, |part| part.cached))
+        .map(|index| (index + 1).to_string())
+        .collect();
+    let fields = [
+        record.day.to_string(),
+        record.example.clone().unwrap_or_default(),
+        part(0).map(|part| part.answer.clone()).unwrap_or_default(),
+        part(1).map(|part| part.answer.clone()).unwrap_or_default(),
+        nanos(record.input).to_string(),
+        nanos(record.parse).to_string(),
+        part(0).map(|part| nanos(part.time).to_string()).unwrap_or_default(),
+        part(1).map(|part| nanos(part.time).to_string()).unwrap_or_default(),
+        nanos(record.total()).to_string(),
+        part(0).and_then(|part| part.check).map(|status| status.to_string()).unwrap_or_default(),
+        part(1).and_then(|part| part.check).map(|status| status.to_string()).unwrap_or_default(),
+        record.error.clone().unwrap_or_default(),
+        skipped.join(" "),
+        record.bench.as_ref().map(|bench| bench.runs.to_string()).unwrap_or_default(),
+        record.compare.as_ref().map(|compare| {
+            let slower: Vec<&str> = compare.changes.iter()
+                .filter(|change| change.slower)
+                .map(|change| change.phase)
+                .collect();
+            slower.join(" ")
+        }).unwrap_or_default(),
+        record.problems.as_ref().map(|problems| problems.len().to_string()).unwrap_or_default(),
+        cached.join(" "),
+    ];
+    fields.iter().map(|field| csv_field(field)).collect::<Vec<String>>().join(",")
+}
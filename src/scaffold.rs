@@ -0,0 +1,57 @@
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use crate::{cli, input};
+
+// src/dNN/mod.rs for a new day, with {N} and {NN} for the day number.
+//
+// The library's tests build it as day 25, so it can't fall behind the Day trait.
+const TEMPLATE: &str = include_str!("new_day.rs.in");
+
+fn module(day: u8) -> String {
+    TEMPLATE.replace("{NN}", &format!("{:02}", day)).replace("{N}", &day.to_string())
+}
+
+// Write src/dNN/mod.rs for day under src, refusing to touch an existing day.
+fn create(src: &Path, day: u8) -> Result<(), Box<dyn Error>> {
+    if !src.is_dir() {
+        return Err(format!("{}: no source directory here (see --src)", src.display()).into());
+    }
+    let dir = src.join(format!("d{:02}", day));
+    if dir.exists() || dir.with_extension("rs").exists() {
+        return Err(format!("{}: day {} already exists", dir.display(), day).into());
+    }
+    fs::create_dir(&dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let path = dir.join("mod.rs");
+    fs::write(&path, module(day)).map_err(|e| format!("{}: {}", path.display(), e))?;
+    println!("Created {}", path.display());
+    Ok(())
+}
+
+// Scaffold a new day's module and make a place for its input.
+pub fn run(day: u8, src: &Path, opts: &cli::Cli) -> Result<(), Box<dyn Error>> {
+    create(src, day)?;
+    let input_dir = input::input_root(opts).join(format!("d{:02}", day));
+    fs::create_dir_all(&input_dir).map_err(|e| format!("{}: {}", input_dir.display(), e))?;
+    println!("Put the input in {} (or let it download)", input_dir.join("input.txt").display());
+    println!("Rebuild to register day {}", day);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_once() {
+        let src = std::env::temp_dir().join(format!("advent2021-scaffold-{}", std::process::id()));
+        fs::create_dir_all(&src).unwrap();
+        create(&src, 7).unwrap();
+        let module = fs::read_to_string(src.join("d07").join("mod.rs")).unwrap();
+        assert!(module.contains("#[advent_day(7)]\npub struct Day7;"));
+        assert!(create(&src, 7).is_err());
+        fs::remove_dir_all(&src).unwrap();
+        assert!(create(&src, 8).is_err());
+    }
+}
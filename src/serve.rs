@@ -0,0 +1,330 @@
+use std::error::Error;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::json;
+
+use advent2021::params::{self, Arg};
+use advent2021::{parse, Solver};
+
+use crate::report;
+
+// Request line plus headers; anything bigger isn't one of ours.
+const MAX_HEAD: usize = 16 * 1024;
+
+// How long a client gets to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Unread request left to discard after an error, and how long to wait for it.
+const MAX_DRAIN: u64 = 16 * 1024 * 1024;
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+struct Request {
+    method: String,
+    path: String,
+    // Query parameters, which become day parameters.
+    args: Vec<Arg>,
+    body: Vec<u8>,
+}
+
+struct Response {
+    status: u16,
+    body: serde_json::Value,
+}
+
+impl Response {
+    fn error(status: u16, message: impl Into<String>) -> Response {
+        Response { status, body: json!({ "error": message.into() }) }
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        411 => "Length Required",
+        413 => "Payload Too Large",
+        422 => "Unprocessable Entity",
+        431 => "Request Header Fields Too Large",
+        500 => "Internal Server Error",
+        501 => "Not Implemented",
+        _ => "Unknown",
+    }
+}
+
+// Read one line of the request head, counting it against MAX_HEAD.
+fn read_head_line(reader: &mut dyn BufRead, head: &mut usize) -> Result<String, Response> {
+    let mut line = Vec::new();
+    let limit = (MAX_HEAD - *head) as u64;
+    reader.take(limit).read_until(b'\n', &mut line)
+        .map_err(|e| Response::error(400, format!("reading request: {}", e)))?;
+    *head += line.len();
+    if !line.ends_with(b"\n") {
+        return Err(match *head >= MAX_HEAD {
+            true => Response::error(431, "request head too large"),
+            false => Response::error(400, "request ended early"),
+        });
+    }
+    String::from_utf8(line)
+        .map(|line| line.trim_end().to_string())
+        .map_err(|_| Response::error(400, "request head is not UTF-8"))
+}
+
+// Undo form encoding: + is a space and %XX a byte.
+fn decode(text: &str) -> Result<String, Response> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, after)) = rest.split_first() {
+        rest = after;
+        bytes.push(match byte {
+            b'+' => b' ',
+            b'%' => {
+                let hex = rest.get(..2).filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+                    .and_then(|hex| std::str::from_utf8(hex).ok());
+                let byte = hex.and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    .ok_or_else(|| Response::error(400, format!("bad escape in '{}'", text)))?;
+                rest = &rest[2..];
+                byte
+            }
+            _ => byte,
+        });
+    }
+    String::from_utf8(bytes).map_err(|_| Response::error(400, format!("'{}' is not UTF-8", text)))
+}
+
+fn parse_query(query: &str) -> Result<Vec<Arg>, Response> {
+    query.split('&')
+        .filter(|param| !param.is_empty())
+        .map(|param| match param.split_once('=') {
+            // Split before decoding, so an escaped = stays part of the name or value.
+            Some((name, value)) => Ok(Arg { name: decode(name)?, value: decode(value)? }),
+            None => Err(Response::error(400, format!("expected NAME=VALUE, found '{}'", param))),
+        })
+        .collect()
+}
+
+fn read_request(reader: &mut dyn BufRead, max_body: usize) -> Result<Request, Response> {
+    let mut head = 0;
+    let request_line = read_head_line(reader, &mut head)?;
+    let mut words = request_line.split_whitespace();
+    let (method, target) = match (words.next(), words.next(), words.next()) {
+        (Some(method), Some(target), Some(version)) if version.starts_with("HTTP/1.") => {
+            (method.to_string(), target)
+        }
+        _ => return Err(Response::error(400, "malformed request line")),
+    };
+    let (path, args) = match target.split_once('?') {
+        Some((path, query)) => (path.to_string(), parse_query(query)?),
+        None => (target.to_string(), Vec::new()),
+    };
+    let mut length = None;
+    loop {
+        let line = read_head_line(reader, &mut head)?;
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line.split_once(':')
+            .ok_or_else(|| Response::error(400, "malformed header"))?;
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => length = Some(value.trim().parse::<usize>()
+                .map_err(|_| Response::error(400, "invalid Content-Length"))?),
+            "transfer-encoding" => return Err(Response::error(501, "send a Content-Length")),
+            _ => (),
+        }
+    }
+    let body = match (method.as_str(), length) {
+        (_, Some(length)) if length > max_body => {
+            return Err(Response::error(413, format!("body over {} bytes", max_body)));
+        }
+        (_, Some(length)) => {
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body)
+                .map_err(|e| Response::error(400, format!("reading body: {}", e)))?;
+            body
+        }
+        ("POST", None) => return Err(Response::error(411, "send a Content-Length")),
+        (_, None) => Vec::new(),
+    };
+    Ok(Request { method, path, args, body })
+}
+
+// Solve a day for a POST /day/N, like a --format json line.
+fn solve(day: usize, solver: &dyn Solver, request: &Request) -> Response {
+    if let Err(error) = params::check(&request.args, &[day - 1]) {
+        return Response::error(400, error);
+    }
+    let mut record = report::Record::new(day);
+    // A panic on odd input mustn't take a worker with it.
+    let solved = panic::catch_unwind(AssertUnwindSafe(|| {
+        solver.solve(&mut request.body.as_slice(), &request.args, None)
+    }));
+    let status = match solved {
+        Ok(Ok(result)) => {
+            record.set_result(result, None);
+            200
+        }
+        Ok(Err(mut error)) => {
+            parse::set_day(&mut error, day as u8);
+            record.error = Some(error.to_string());
+            match parse::errors(error) {
+                Ok(problems) => {
+                    record.problems = Some(problems);
+                    422
+                }
+                Err(_) => 500,
+            }
+        }
+        Err(_) => {
+            record.error = Some("solution panicked".to_string());
+            500
+        }
+    };
+    Response { status, body: report::to_json(&record) }
+}
+
+fn route(request: &Request) -> Response {
+    let day = match request.path.strip_prefix("/day/") {
+        Some(day) => day,
+        None => return Response::error(404, "try POST /day/N"),
+    };
+    let solver = day.parse::<usize>().ok()
+        .and_then(|day| Some((day, advent2021::solver(day.checked_sub(1)?)?)));
+    let (day, solver) = match solver {
+        Some(solver) => solver,
+        None => return Response::error(404, format!("no solution for day {}", day)),
+    };
+    if request.method != "POST" {
+        return Response::error(405, "POST the input to /day/N");
+    }
+    solve(day, solver, request)
+}
+
+fn write_response(stream: &mut TcpStream, response: &Response) -> io::Result<()> {
+    let body = response.body.to_string();
+    write!(stream, "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+                    Connection: close\r\n\r\n{}\n",
+           response.status, reason(response.status), body.len() + 1, body)?;
+    stream.flush()
+}
+
+// Finish the response, then discard what the client is still sending.
+//
+// Closing with unread data resets the connection, and the client may lose
+// the response (say, a 413) before reading it.
+fn drain(stream: &mut TcpStream) {
+    let _ = stream.shutdown(Shutdown::Write);
+    let _ = stream.set_read_timeout(Some(DRAIN_TIMEOUT));
+    let _ = io::copy(&mut stream.take(MAX_DRAIN), &mut io::sink());
+}
+
+fn handle(mut stream: TcpStream, max_body: usize) {
+    let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+    let (response, unread) = match stream.try_clone() {
+        Ok(reader) => match read_request(&mut BufReader::new(reader), max_body) {
+            Ok(request) => (route(&request), false),
+            Err(response) => (response, true),
+        },
+        Err(error) => (Response::error(500, error.to_string()), false),
+    };
+    if let Err(error) = write_response(&mut stream, &response) {
+        eprintln!("ERROR: writing response: {}", error);
+    } else if unread {
+        drain(&mut stream);
+    }
+}
+
+// Serve POST /day/N until killed, handling up to workers requests at once.
+pub fn run(address: &str, port: u16, workers: usize, max_body: usize)
+    -> Result<(), Box<dyn Error>>
+{
+    let listener = TcpListener::bind((address, port))
+        .map_err(|e| format!("{}:{}: {}", address, port, e))?;
+    eprintln!("Listening on http://{}", listener.local_addr()?);
+    let (sender, receiver) = mpsc::channel::<TcpStream>();
+    let receiver = Arc::new(Mutex::new(receiver));
+    for _ in 0..workers.max(1) {
+        let receiver = Arc::clone(&receiver);
+        thread::spawn(move || loop {
+            let stream = receiver.lock().unwrap().recv();
+            match stream {
+                Ok(stream) => handle(stream, max_body),
+                Err(_) => break,
+            }
+        });
+    }
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => sender.send(stream)?,
+            Err(error) => eprintln!("ERROR: accepting connection: {}", error),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(text: &str) -> Result<Request, Response> {
+        read_request(&mut text.as_bytes(), 32)
+    }
+
+    #[test]
+    fn solve_posted_input() {
+        let request = read("POST /day/6?days=18 HTTP/1.1\r\nContent-Length: 10\r\n\r\n3,4,3,1,2\n")
+            .ok().unwrap();
+        let response = route(&request);
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body["part1"]["answer"], "26");
+        let request = read("POST /day/1 HTTP/1.1\r\nContent-Length: 4\r\n\r\n1\nx\n").ok().unwrap();
+        let response = route(&request);
+        assert_eq!(response.status, 422);
+        assert_eq!(response.body["problems"][0]["line"], 2);
+    }
+
+    #[test]
+    fn bad_requests() {
+        let status = |text| read(text).err().unwrap().status;
+        assert_eq!(status("POST /day/1 HTTP/1.1\r\nContent-Length: 33\r\n\r\n"), 413);
+        assert_eq!(status("POST /day/1 HTTP/1.1\r\n\r\n"), 411);
+        assert_eq!(status("POST /day/1\r\n\r\n"), 400);
+        assert_eq!(status("POST /day/1 HTTP/1.1\r\nContent-Length: 5\r\n\r\n1\n"), 400);
+        let route_status = |text| route(&read(text).ok().unwrap()).status;
+        assert_eq!(route_status("GET /day/1 HTTP/1.1\r\n\r\n"), 405);
+        assert_eq!(route_status("POST /day/30 HTTP/1.1\r\nContent-Length: 0\r\n\r\n"), 404);
+        assert_eq!(route_status("POST /day/6?days=x HTTP/1.1\r\nContent-Length: 0\r\n\r\n"), 400);
+        assert_eq!(status("POST /day/6?days=%+1 HTTP/1.1\r\nContent-Length: 0\r\n\r\n"), 400);
+        assert_eq!(status("POST /day/6?days=%ff HTTP/1.1\r\nContent-Length: 0\r\n\r\n"), 400);
+    }
+
+    #[test]
+    fn too_large_is_answered_not_reset() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || handle(listener.accept().unwrap().0, 32));
+        let mut client = TcpStream::connect(address).unwrap();
+        let body = vec![b'1'; 64 * 1024];
+        write!(client, "POST /day/1 HTTP/1.1\r\nContent-Length: {}\r\n\r\n", body.len()).unwrap();
+        client.write_all(&body).unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 413 "), "{}", response);
+        drop(client);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn query_is_decoded() {
+        assert_eq!(decode("a+b%2Cc%3d").ok().unwrap(), "a b,c=");
+        let request = read("POST /day/6?days=1%38&a%3Db=c+d HTTP/1.1\r\nContent-Length: 0\r\n\r\n").ok().unwrap();
+        let args: Vec<(&str, &str)> = request.args.iter()
+            .map(|arg| (arg.name.as_str(), arg.value.as_str())).collect();
+        assert_eq!(args, [("days", "18"), ("a=b", "c d")]);
+    }
+}
@@ -0,0 +1,115 @@
+use std::cell::Cell;
+use std::str::FromStr;
+
+// How much a day says about what it's doing, from least to most.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Off,
+    // A few lines per part: intermediate results worth knowing.
+    Summary,
+    // A line or so per step of the solution: each fold, each simulated day.
+    Step,
+    // Everything, like every cell visited; can be megabytes on real inputs.
+    Detail,
+}
+
+impl FromStr for Level {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Level, String> {
+        match s {
+            "off" => Ok(Level::Off),
+            "summary" => Ok(Level::Summary),
+            "step" => Ok(Level::Step),
+            "detail" => Ok(Level::Detail),
+            _ => Err(format!("invalid trace level '{}' (off, summary, step, or detail)", s)),
+        }
+    }
+}
+
+// One -v argument: a level for every day, or for just one day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    pub day: Option<u8>,
+    pub level: Level,
+}
+
+impl FromStr for Filter {
+    type Err = String;
+    // LEVEL or DAY=LEVEL.
+    fn from_str(s: &str) -> Result<Filter, String> {
+        match s.split_once('=') {
+            None => Ok(Filter { day: None, level: s.parse()? }),
+            Some((day, level)) => {
+                let day = day.parse().map_err(|_| format!("invalid day '{}' in -v {}", day, s))?;
+                Ok(Filter { day: Some(day), level: level.parse()? })
+            }
+        }
+    }
+}
+
+// The level for day given the -v filters; later filters take precedence.
+//
+// A bare -v means summary for every day. With only per-day filters, other days are off.
+pub fn level(filters: &Option<Vec<Filter>>, day: u8) -> Level {
+    match filters {
+        None => Level::Off,
+        Some(filters) if filters.is_empty() => Level::Summary,
+        Some(filters) => filters.iter().rev()
+            .find(|filter| filter.day.map_or(true, |d| d == day))
+            .map_or(Level::Off, |filter| filter.level),
+    }
+}
+
+thread_local! {
+    // Level of the day running on this thread.
+    static LEVEL: Cell<Level> = const { Cell::new(Level::Off) };
+}
+
+// Run f with tracing at level on this thread.
+pub fn with_level<T>(level: Level, f: impl FnOnce() -> T) -> T {
+    let outer = LEVEL.with(|current| current.replace(level));
+    let result = f();
+    LEVEL.with(|current| current.set(outer));
+    result
+}
+
+pub fn enabled(level: Level) -> bool {
+    LEVEL.with(|current| level <= current.get())
+}
+
+// vprintln! at a trace level, like trace!(Step, "folded {} points", n).
+//
+// Nothing is formatted unless the running day is traced at that level.
+macro_rules! trace {
+    ($level:ident, $($arg:tt)*) => {
+        if $crate::trace::enabled($crate::trace::Level::$level) {
+            vprintln!($($arg)*);
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn levels(filters: &[&str]) -> Vec<Level> {
+        let filters = Some(filters.iter().map(|filter| filter.parse().unwrap()).collect());
+        [1, 12].iter().map(|day| level(&filters, *day)).collect()
+    }
+
+    #[test]
+    fn later_filters_take_precedence() {
+        assert_eq!(level(&None, 1), Level::Off);
+        assert_eq!(levels(&[]), [Level::Summary, Level::Summary]);
+        assert_eq!(levels(&["summary", "12=detail"]), [Level::Summary, Level::Detail]);
+        assert_eq!(levels(&["12=detail", "step"]), [Level::Step, Level::Step]);
+        assert_eq!(levels(&["12=step"]), [Level::Off, Level::Step]);
+    }
+
+    #[test]
+    fn bad_filters() {
+        for bad in ["", "loud", "x=step", "12=", "12=loud"] {
+            assert!(bad.parse::<Filter>().is_err(), "{:?} should not parse", bad);
+        }
+    }
+}
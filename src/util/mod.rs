@@ -1,83 +1,162 @@
-use std::io::{BufRead, BufReader};
-use std::fs::File;
-use std::path::{Path, PathBuf};
-use std::mem::swap;
-use std::error::Error;
+use std::io::{self, BufRead};
+use std::fs;
+use std::path::Path;
+use std::fmt;
+use std::fmt::Write;
+use std::cell::RefCell;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use crate::cli;
+use crate::parse::{self, ParseError, ParseErrors};
 
 pub mod vec2d;
 
-fn input_path(opts: &cli::Cli, mod_path: &str) -> PathBuf {
-    match &opts.input {
-        Some(path) => path.clone(),
-        // src/dXX/mod.rs -> src/dXX/input.txt
-        None => Path::new(mod_path).parent().unwrap().join("input.txt"),
+static VERBOSE_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+// Send verbose output to stderr instead of stdout.
+pub fn set_verbose_to_stderr(to_stderr: bool) {
+    VERBOSE_TO_STDERR.store(to_stderr, Ordering::Relaxed);
+}
+
+thread_local! {
+    // Verbose output captured on this thread, if capturing.
+    static VERBOSE_CAPTURE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+pub fn verbose_print(args: fmt::Arguments) {
+    let captured = VERBOSE_CAPTURE.with(|capture| match capture.borrow_mut().as_mut() {
+        Some(buffer) => { let _ = buffer.write_fmt(args); true }
+        None => false,
+    });
+    if !captured {
+        print_verbose(&args.to_string());
     }
 }
 
-pub fn read_input(opts: &cli::Cli, mod_path: &str) -> Result<Box<dyn BufRead>, std::io::Error> {
-    Ok(Box::new(BufReader::new(File::open(input_path(opts, mod_path))?)))
+static TRACE_FILE: Mutex<Option<io::BufWriter<fs::File>>> = Mutex::new(None);
+
+// Send verbose output to a new file at path instead (--trace-file).
+pub fn set_trace_file(path: &Path) -> io::Result<()> {
+    *TRACE_FILE.lock().unwrap() = Some(io::BufWriter::new(fs::File::create(path)?));
+    Ok(())
 }
 
-// true for a surprising number of days
-const INPUT_LEN_GUESS: usize = 1000;
+// Write out anything buffered for the trace file, if any.
+pub fn flush_trace_file() {
+    if let Some(file) = TRACE_FILE.lock().unwrap().as_mut() {
+        if let Err(error) = io::Write::flush(file) {
+            eprintln!("ERROR: writing trace file: {}", error);
+        }
+    }
+}
 
-// Read one comma-separated line from the input.
-pub fn read_csv(input: &mut dyn BufRead) -> Result<Vec<usize>, Box<dyn Error>> {
-    let mut buffer = String::new();
-    input.read_to_string(&mut buffer)?;
-    let mut input = Vec::<usize>::with_capacity(INPUT_LEN_GUESS);
-    for num_string in buffer.split(",") {
-        input.push(num_string.trim().parse()?);
+// Print verbose output (possibly captured earlier) where it belongs.
+pub fn print_verbose(output: &str) {
+    if let Some(file) = TRACE_FILE.lock().unwrap().as_mut() {
+        let _ = io::Write::write_all(file, output.as_bytes());
+    } else if VERBOSE_TO_STDERR.load(Ordering::Relaxed) {
+        eprint!("{}", output);
+    } else {
+        print!("{}", output);
     }
-    Ok(input)
 }
 
-pub struct SplitGroup<'a> {
-    reader: &'a mut dyn BufRead,
-    buffer: String,
+// Run f, collecting its verbose output instead of printing it.
+//
+// Lets days run on worker threads without their output interleaving.
+//
+// Captures nest: the outer capture resumes once f returns.
+pub fn capture_verbose<T>(f: impl FnOnce() -> T) -> (T, String) {
+    let outer = VERBOSE_CAPTURE.with(|capture| capture.replace(Some(String::new())));
+    let result = f();
+    let output = VERBOSE_CAPTURE.with(|capture| capture.replace(outer));
+    (result, output.unwrap_or_default())
 }
 
-impl Iterator for SplitGroup<'_> {
-    type Item = String;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        self.buffer.clear();
-        let mut bytes_read = 0;
-        while let Some(count) = self.reader.read_line(&mut self.buffer).ok() {
-            bytes_read = count;
-            if count == 1 {
-                self.buffer.pop().unwrap();
-            }
-            if count <= 1 {
-                break
-            }
-        }
-        if bytes_read > 0 {
-            let mut new_buffer = String::new();
-            swap(&mut new_buffer, &mut self.buffer);
-            Some(new_buffer)
+// print! and println! for verbose output from days.
+//
+// Verbose output moves to stderr when stdout is reserved for --format json/csv.
+macro_rules! vprint {
+    ($($arg:tt)*) => { $crate::util::verbose_print(format_args!($($arg)*)) };
+}
+
+macro_rules! vprintln {
+    () => { vprint!("\n") };
+    ($fmt:literal $($arg:tt)*) => { vprint!(concat!($fmt, "\n") $($arg)*) };
+}
+
+// true for a surprising number of days
+const INPUT_LEN_GUESS: usize = 1000;
+
+// Read one comma-separated line of numbers from the input.
+pub fn read_csv(input: &mut dyn BufRead) -> Result<Vec<usize>, ParseErrors> {
+    read_csv_upto(input, usize::MAX)
+}
+
+// Read one comma-separated line of numbers, none greater than max.
+pub fn read_csv_upto(input: &mut dyn BufRead, max: usize) -> Result<Vec<usize>, ParseErrors> {
+    let mut lines = parse::lines(input);
+    let line = lines.next().ok_or_else(|| ParseError::input("empty input"))??;
+    let mut input = Vec::<usize>::with_capacity(INPUT_LEN_GUESS);
+    let mut errors = ParseErrors::default();
+    for num_string in line.text.split(",") {
+        let num_string = num_string.trim();
+        let num = line.parse::<usize>(num_string, "number").and_then(|num| if num > max {
+            Err(line.error(num_string, format!("number exceeds {}", max)))
         } else {
-            None
-        }
+            Ok(num)
+        });
+        input.extend(errors.check(num));
     }
+    for line in lines {
+        let line = line.and_then(|line| if line.text.trim().is_empty() {
+            Ok(())
+        } else {
+            Err(line.error_line("expected a single line of numbers"))
+        });
+        errors.check(line);
+    }
+    errors.or(input)
 }
 
-pub fn split_groups(input: &mut dyn BufRead) -> SplitGroup {
-    SplitGroup { reader: input, buffer: String::new() }
+// The middle of sorted input, or None if it's empty.
+pub fn median<T>(input: &[T]) -> Option<&T> {
+    // assert_eq!(input.is_sorted(), true);
+    input.get(input.len() / 2)
 }
 
-pub fn median<T>(input: &Vec<T>) -> &T {
-    // assert_eq!(input.is_sorted(), true);
-    return &input[input.len() / 2];
+// Read a rectangular grid of digits.
+fn check_grid_row(line: &parse::Line, width: &mut Option<usize>) -> Result<(), ParseError> {
+    if let Some(bad) = line.invalid_char(|c| c.is_ascii_digit()) {
+        return Err(line.error(bad, "expected digit"));
+    }
+    match *width {
+        None if line.text.is_empty() => Err(line.error_line("empty grid row")),
+        None => { *width = Some(line.text.len()); Ok(()) }
+        Some(width) if width != line.text.len() => Err(line.error_line(
+            format!("grid row has {} digits, expected {}", line.text.len(), width))),
+        Some(_) => Ok(()),
+    }
 }
 
-pub fn read_grid(input: &mut dyn BufRead) -> Result<vec2d::Vec2d<u8>, Box<dyn Error>> {
-    let mut lines = input.lines().peekable();
-    let width = lines.peek().ok_or("empty input")?.as_ref().map_err(|e| e.to_string())?.len();
-    Ok(lines.flat_map(|s| s.expect("EOF").into_bytes())
-       .map(|b| b - b'0')
+pub fn read_grid(input: &mut dyn BufRead) -> Result<vec2d::Vec2d<u8>, ParseErrors> {
+    let mut digits = Vec::with_capacity(INPUT_LEN_GUESS);
+    let mut width = None;
+    let mut errors = ParseErrors::default();
+    for line in parse::lines(input) {
+        let line = line.and_then(|line| check_grid_row(&line, &mut width).map(|_| line));
+        if let Some(line) = errors.check(line) {
+            digits.extend(line.text.bytes().map(|b| b - b'0'));
+        }
+    }
+    let width = match width {
+        Some(width) => errors.or(width)?,
+        None if errors.0.is_empty() => return Err(ParseError::input("empty input").into()),
+        None => return Err(errors),
+    };
+    let rows = digits.len() / width;
+    Ok(digits.into_iter()
        .collect::<vec2d::Vec2d<u8>>()
-       .reshaped_from(|(_, len)| (len / width, width))?)
+       .reshaped_from(|_| (rows, width))
+       .expect("grid rows are all the same width"))
 }
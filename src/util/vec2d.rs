@@ -64,6 +64,7 @@ impl<T> Vec2d<T> {
     pub fn nrows(&self) -> usize { self.shape.0 }
     pub fn ncols(&self) -> usize { self.shape.1 }
     pub fn len(&self) -> usize { self.data.len() }
+    pub fn is_empty(&self) -> bool { self.data.is_empty() }
 
     fn _index(&self, (row, col): (usize, usize)) -> usize {
         row * self.shape.1 + col
@@ -163,7 +164,7 @@ impl<T> Display for Vec2d<T>
             for value in row {
                 write!(f, " {:2}", value)?;
             }
-            write!(f, "\n")?;
+            writeln!(f)?;
         }
         Ok(())
     }
@@ -0,0 +1,106 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use advent2021::util;
+
+use crate::report::{self, Format};
+use crate::{cli, input};
+
+// How often to check the inputs for changes.
+const POLL: Duration = Duration::from_millis(500);
+
+// What's known of a file without reading it; None if it's missing.
+type Stamp = Option<(SystemTime, u64)>;
+
+fn stamp(path: &PathBuf) -> Stamp {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+// Each part's answer, or None if it wasn't answered.
+type Answers = BTreeMap<(usize, usize), Option<String>>;
+
+fn answers(records: &[report::Record]) -> Answers {
+    let mut answers = Answers::new();
+    for record in records {
+        for (index, part) in record.parts.iter().enumerate() {
+            answers.insert((record.day, index + 1), part.as_ref().map(|part| part.answer.clone()));
+        }
+    }
+    answers
+}
+
+// Lines describing each answer which differs between runs.
+fn changes(previous: &Answers, answers: &Answers) -> Vec<String> {
+    let none = "(none)".to_string();
+    answers.iter()
+        .filter(|(key, answer)| previous.get(key).map_or(false, |previous| previous != *answer))
+        .map(|((day, part), answer)| {
+            let previous = previous[&(*day, *part)].as_ref().unwrap_or(&none);
+            format!("  Day {} part {}: {} -> {}", day, part, previous, answer.as_ref().unwrap_or(&none))
+        })
+        .collect()
+}
+
+// Run the selected days, then again whenever one of their inputs changes, forever.
+pub fn run(opts: &cli::Cli) -> Result<(), Box<dyn Error>> {
+    let paths = opts.day.iter()
+        .map(|day| input::watch_path(opts, day))
+        .collect::<Result<Vec<PathBuf>, _>>()?;
+    let text = opts.format == Format::Text;
+    let mut previous: Option<Answers> = None;
+    loop {
+        if text {
+            // Clear the screen and go home.
+            print!("\x1b[2J\x1b[H");
+        }
+        // Stamp before running, so a change made mid-run triggers another.
+        let stamps: Vec<Stamp> = paths.iter().map(stamp).collect();
+        match crate::run(opts) {
+            Ok((outcome, records)) => {
+                crate::print_outcome(&outcome);
+                let answers = answers(&records);
+                if let Some(previous) = &previous {
+                    let changes = changes(previous, &answers);
+                    // Keep stdout parseable for machine-readable formats.
+                    let mut summary = match changes.len() {
+                        0 => "No answers changed since the last run.".to_string(),
+                        _ => format!("Changed since the last run:\n{}", changes.join("\n")),
+                    };
+                    summary += "\n";
+                    if text { print!("{}", summary) } else { eprint!("{}", summary) }
+                }
+                previous = Some(answers);
+            }
+            Err(error) => eprintln!("ERROR: {}", error),
+        }
+        util::flush_trace_file();
+        let names: Vec<String> = paths.iter().map(|path| path.display().to_string()).collect();
+        eprintln!("Watching {} (Ctrl-C to stop)", names.join(", "));
+        while paths.iter().map(stamp).eq(stamps.iter().cloned()) {
+            thread::sleep(POLL);
+        }
+        // Editors often save in several steps; let them finish.
+        thread::sleep(POLL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_changed_answers_are_listed() {
+        let answers = |part1: &str, part2: Option<&str>| -> Answers {
+            vec![((7, 1), Some(part1.to_string())), ((7, 2), part2.map(String::from))].into_iter().collect()
+        };
+        assert!(changes(&answers("37", Some("168")), &answers("37", Some("168"))).is_empty());
+        assert_eq!(changes(&answers("37", Some("168")), &answers("38", None)),
+                   ["  Day 7 part 1: 37 -> 38", "  Day 7 part 2: 168 -> (none)"]);
+        assert!(changes(&Answers::new(), &answers("37", None)).is_empty());
+    }
+}
@@ -0,0 +1,184 @@
+use std::env;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+pub const SESSION_ENV: &str = "AOC_SESSION";
+
+const YEAR: u16 = 2021;
+const USER_AGENT: &str = concat!(
+    "advent2021/", env!("CARGO_PKG_VERSION"), " (github.com/fritzr/advent2021)"
+);
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+pub enum FetchError {
+    // No session token in the environment or config file.
+    NoSession(PathBuf),
+    // Server rejected the session token (400/401/403).
+    Unauthorized(u16),
+    // Puzzle doesn't exist or isn't unlocked yet (404).
+    NotFound(u8),
+    // Any other HTTP status.
+    Status(u16, String),
+    // Couldn't talk to the server at all.
+    Transport(String),
+    // Couldn't save the downloaded input.
+    Io(PathBuf, io::Error),
+}
+
+impl Display for FetchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            FetchError::NoSession(config) => write!(f,
+                "no session token: set ${} or write it to {}", SESSION_ENV, config.display()),
+            FetchError::Unauthorized(code) => write!(f,
+                "session token rejected (HTTP {}): log in again and update your token", code),
+            FetchError::NotFound(day) => write!(f,
+                "day {} input not found (HTTP 404): puzzle may not be unlocked yet", day),
+            FetchError::Status(code, text) => write!(f, "server returned HTTP {}: {}", code, text),
+            FetchError::Transport(text) => write!(f, "request failed: {}", text),
+            FetchError::Io(path, error) => write!(f, "cannot write {}: {}", path.display(), error),
+        }
+    }
+}
+
+impl Error for FetchError {}
+
+// ~/.config/advent2021/session, respecting $XDG_CONFIG_HOME.
+fn session_config_path() -> PathBuf {
+    let config = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME").unwrap_or_default()).join(".config"),
+    };
+    config.join("advent2021").join("session")
+}
+
+// Find the session token, first from $AOC_SESSION then from the config file.
+pub fn session_token() -> Result<String, FetchError> {
+    if let Ok(token) = env::var(SESSION_ENV) {
+        if !token.trim().is_empty() {
+            return Ok(token.trim().to_string());
+        }
+    }
+    let config = session_config_path();
+    match fs::read_to_string(&config) {
+        Ok(token) if !token.trim().is_empty() => Ok(token.trim().to_string()),
+        _ => Err(FetchError::NoSession(config)),
+    }
+}
+
+// Download the raw input text for a day, logged in with the session token.
+pub fn fetch_input(base_url: &str, token: &str, day: u8) -> Result<String, FetchError> {
+    let url = format!("{}/{}/day/{}/input", base_url.trim_end_matches('/'), YEAR, day);
+    let response = ureq::get(&url)
+        .timeout(TIMEOUT)
+        .set("User-Agent", USER_AGENT)
+        .set("Cookie", &format!("session={}", token))
+        .call();
+    match response {
+        Ok(response) => response.into_string()
+            .map_err(|e| FetchError::Transport(e.to_string())),
+        Err(ureq::Error::Status(code @ (400 | 401 | 403), _)) => Err(FetchError::Unauthorized(code)),
+        Err(ureq::Error::Status(404, _)) => Err(FetchError::NotFound(day)),
+        Err(ureq::Error::Status(code, response)) => {
+            let text = response.into_string().unwrap_or_default();
+            Err(FetchError::Status(code, text.trim().to_string()))
+        }
+        Err(ureq::Error::Transport(error)) => Err(FetchError::Transport(error.to_string())),
+    }
+}
+
+// Write to a temporary file beside the target, then rename over it.
+//
+// Partial downloads never leave a truncated input.txt behind.
+fn write_atomic(path: &Path, contents: &str) -> Result<(), io::Error> {
+    if let Some(dir) = path.parent() {
+        if !dir.as_os_str().is_empty() {
+            fs::create_dir_all(dir)?;
+        }
+    }
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".part");
+    let tmp_path = path.with_file_name(tmp_name);
+    let result = fs::File::create(&tmp_path)
+        .and_then(|mut file| { file.write_all(contents.as_bytes())?; file.sync_all() })
+        .and_then(|_| fs::rename(&tmp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+// Fetch a day's input and cache it at path.
+pub fn fetch_to(base_url: &str, token: &str, day: u8, path: &Path) -> Result<(), FetchError> {
+    let input = fetch_input(base_url, token, day)?;
+    write_atomic(path, &input).map_err(|e| FetchError::Io(path.to_path_buf(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    use std::thread;
+
+    // Serve one canned response on a local port, returning its base URL and
+    // the request head the client sent.
+    fn stand_in(status: &str, body: &'static str) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let status = status.to_string();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut head = Vec::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                head.push(line.trim().to_string());
+            }
+            write!(reader.get_mut(), "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                   status, body.len(), body).unwrap();
+            head.join("\n")
+        });
+        (base_url, server)
+    }
+
+    #[test]
+    fn fetch_from_stand_in() {
+        let dir = env::temp_dir().join(format!("advent2021-web-{}", std::process::id()));
+        let path = dir.join("d07").join("input.txt");
+        let part = dir.join("d07").join("input.txt.part");
+
+        let (base_url, server) = stand_in("200 OK", "16,1,2,0,4,2,7,1,2,14\n");
+        fetch_to(&base_url, "c0ffee", 7, &path).unwrap();
+        let head = server.join().unwrap();
+        assert!(head.starts_with("GET /2021/day/7/input HTTP/1.1"), "{}", head);
+        assert!(head.contains("Cookie: session=c0ffee"), "{}", head);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "16,1,2,0,4,2,7,1,2,14\n");
+        assert!(!part.exists());
+
+        // Failures leave what was already there alone.
+        let (base_url, server) = stand_in("401 Unauthorized", "");
+        let error = fetch_to(&base_url, "c0ffee", 7, &path).unwrap_err();
+        server.join().unwrap();
+        assert_eq!(error.to_string(),
+                   "session token rejected (HTTP 401): log in again and update your token");
+        let (base_url, server) = stand_in("404 Not Found", "Not found\n");
+        let error = fetch_to(&base_url, "c0ffee", 25, &path).unwrap_err();
+        server.join().unwrap();
+        assert_eq!(error.to_string(),
+                   "day 25 input not found (HTTP 404): puzzle may not be unlocked yet");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "16,1,2,0,4,2,7,1,2,14\n");
+        assert!(!part.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}